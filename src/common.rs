@@ -1,10 +1,9 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::collections::HashMap;
 
 use color_eyre::eyre::{ContextCompat, WrapErr};
 use console::{style, Style};
 use futures::StreamExt;
-use glob::glob;
-use near_cli_rs::common::CallResultExt;
+use near_cli_rs::common::{CallResultExt, JsonRpcClientExt};
 use near_primitives::types::AccountId;
 use serde::de::{Deserialize, Deserializer};
 use similar::{ChangeTag, TextDiff};
@@ -22,67 +21,511 @@ impl std::fmt::Display for Line {
     }
 }
 
-pub struct DiffCodeError;
+/// Line-based difference between two versions of the component code
+#[derive(Debug, Clone)]
+pub struct CodeDiff {
+    old_code: String,
+    new_code: String,
+}
 
-pub fn diff_code(old_code: &str, new_code: &str) -> Result<(), DiffCodeError> {
-    let old_code = old_code.trim();
-    let new_code = new_code.trim();
-    if old_code == new_code {
-        return Ok(());
+impl CodeDiff {
+    /// Prints the changes with line numbers and highlighted inline edits
+    pub fn print(&self) {
+        let diff = TextDiff::from_lines(&self.old_code, &self.new_code);
+
+        for (idx, group) in diff.grouped_ops(3).iter().enumerate() {
+            if idx > 0 {
+                println!("{:-^1$}", "-", 80);
+            }
+            for op in group {
+                for change in diff.iter_inline_changes(op) {
+                    let (sign, s) = match change.tag() {
+                        ChangeTag::Delete => ("-", Style::new().red()),
+                        ChangeTag::Insert => ("+", Style::new().green()),
+                        ChangeTag::Equal => (" ", Style::new().dim()),
+                    };
+                    print!(
+                        "{}{} |{}",
+                        style(Line(change.old_index())).dim(),
+                        style(Line(change.new_index())).dim(),
+                        s.apply_to(sign).bold(),
+                    );
+                    for (emphasized, value) in change.iter_strings_lossy() {
+                        if emphasized {
+                            print!("{}", s.apply_to(value).underlined().on_black());
+                        } else {
+                            print!("{}", s.apply_to(value));
+                        }
+                    }
+                    if change.missing_newline() {
+                        println!();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Unified diff (as produced by `diff -u`) between the `old_path` and `new_path` files
+    pub fn unified(&self, old_path: &str, new_path: &str) -> String {
+        // Both sides end with a newline, so the patch has no "No newline at end of file" markers
+        let old_code = with_trailing_newline(&self.old_code);
+        let new_code = with_trailing_newline(&self.new_code);
+        TextDiff::from_lines(&old_code, &new_code)
+            .unified_diff()
+            .context_radius(3)
+            .header(old_path, new_path)
+            .to_string()
     }
+}
+
+/// Prints the changes between the exact file contents as a unified diff (unlike [`diff_code`],
+/// the contents are not trimmed and a missing newline at the end of a file is marked), so that
+/// `git apply` applies the patch to the files with the old contents
+pub fn unified_patch(
+    old_content: &str,
+    new_content: &str,
+    old_path: &str,
+    new_path: &str,
+) -> String {
+    TextDiff::from_lines(old_content, new_content)
+        .unified_diff()
+        .context_radius(3)
+        .header(old_path, new_path)
+        .to_string()
+}
 
-    let diff = TextDiff::from_lines(old_code, new_code);
+fn with_trailing_newline(code: &str) -> String {
+    if code.is_empty() || code.ends_with('\n') {
+        code.to_string()
+    } else {
+        format!("{code}\n")
+    }
+}
 
-    for (idx, group) in diff.grouped_ops(3).iter().enumerate() {
-        if idx > 0 {
-            println!("{:-^1$}", "-", 80);
+/// Which differences in the code are not considered changes
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DiffOptions {
+    /// Ignore indentation, trailing whitespace, empty lines and the length of whitespace runs
+    /// inside lines (like `git diff -b --ignore-blank-lines`); removed or added whitespace
+    /// between two non-whitespace characters is still a change, and so is any whitespace change
+    /// inside string and template literals, as it changes the rendered text
+    pub ignore_whitespace: bool,
+    /// Ignore CRLF vs LF line endings
+    pub ignore_line_endings: bool,
+}
+
+impl DiffOptions {
+    /// The code with the differences that must be ignored normalized away
+    fn normalize<'a>(&self, code: &'a str) -> std::borrow::Cow<'a, str> {
+        let code: std::borrow::Cow<'a, str> = if self.ignore_line_endings && code.contains('\r') {
+            code.replace("\r\n", "\n").into()
+        } else {
+            code.into()
+        };
+        if self.ignore_whitespace {
+            normalize_whitespace(&code).into()
+        } else {
+            code
         }
-        for op in group {
-            for change in diff.iter_inline_changes(op) {
-                let (sign, s) = match change.tag() {
-                    ChangeTag::Delete => ("-", Style::new().red()),
-                    ChangeTag::Insert => ("+", Style::new().green()),
-                    ChangeTag::Equal => (" ", Style::new().dim()),
-                };
-                print!(
-                    "{}{} |{}",
-                    style(Line(change.old_index())).dim(),
-                    style(Line(change.new_index())).dim(),
-                    s.apply_to(sign).bold(),
-                );
-                for (emphasized, value) in change.iter_strings_lossy() {
-                    if emphasized {
-                        print!("{}", s.apply_to(value).underlined().on_black());
-                    } else {
-                        print!("{}", s.apply_to(value));
-                    }
+    }
+}
+
+/// Collapses the whitespace of the code outside string and template literals: indentation,
+/// trailing whitespace and empty lines are removed, and whitespace runs inside lines are
+/// replaced with a single space
+fn normalize_whitespace(code: &str) -> String {
+    enum Context {
+        /// Code (the top level or a `${...}` expression of a template literal) with the
+        /// number of the open braces
+        Code(usize),
+        /// String literal with its quote
+        String(char),
+        Template,
+        LineComment,
+        BlockComment,
+    }
+    let mut contexts = vec![Context::Code(0)];
+    let mut normalized = String::with_capacity(code.len());
+    // A newline wins over spaces, so indentation and trailing whitespace are dropped
+    let mut pending_whitespace = None;
+    let mut chars = code.chars().peekable();
+    while let Some(c) = chars.next() {
+        let context = contexts
+            .last_mut()
+            .expect("Internal error: no code context");
+        match context {
+            Context::String(quote) => {
+                let quote = *quote;
+                normalized.push(c);
+                if c == '\\' {
+                    normalized.extend(chars.next());
+                } else if c == quote || c == '\n' {
+                    contexts.pop();
                 }
-                if change.missing_newline() {
-                    println!();
+                continue;
+            }
+            Context::Template => {
+                normalized.push(c);
+                if c == '\\' {
+                    normalized.extend(chars.next());
+                } else if c == '`' {
+                    contexts.pop();
+                } else if c == '$' && chars.peek() == Some(&'{') {
+                    normalized.extend(chars.next());
+                    contexts.push(Context::Code(0));
+                }
+                continue;
+            }
+            Context::Code(_) | Context::LineComment | Context::BlockComment => {}
+        }
+
+        if c.is_whitespace() {
+            if c == '\n' {
+                pending_whitespace = Some('\n');
+                if let Context::LineComment = context {
+                    contexts.pop();
+                }
+            } else if pending_whitespace.is_none() {
+                pending_whitespace = Some(' ');
+            }
+            continue;
+        }
+        if let Some(whitespace) = pending_whitespace.take() {
+            if !normalized.is_empty() {
+                normalized.push(whitespace);
+            }
+        }
+        normalized.push(c);
+        match context {
+            Context::Code(open_braces) => match c {
+                '\'' | '"' => contexts.push(Context::String(c)),
+                '`' => contexts.push(Context::Template),
+                '/' if chars.peek() == Some(&'/') => contexts.push(Context::LineComment),
+                '/' if chars.peek() == Some(&'*') => {
+                    normalized.extend(chars.next());
+                    contexts.push(Context::BlockComment);
                 }
+                '{' => *open_braces += 1,
+                '}' if *open_braces > 0 => *open_braces -= 1,
+                // The end of a `${...}` expression
+                '}' if contexts.len() > 1 => {
+                    contexts.pop();
+                }
+                _ => {}
+            },
+            Context::BlockComment if c == '*' && chars.peek() == Some(&'/') => {
+                normalized.extend(chars.next());
+                contexts.pop();
+            }
+            _ => {}
+        }
+    }
+    normalized
+}
+
+/// Returns `None` if the code is the same (leading and trailing whitespace is always ignored).
+/// The returned diff contains all the changes, including the ignored ones.
+pub fn diff_code(old_code: &str, new_code: &str, options: &DiffOptions) -> Option<CodeDiff> {
+    let old_code = old_code.trim();
+    let new_code = new_code.trim();
+    if options.normalize(old_code) == options.normalize(new_code) {
+        return None;
+    }
+    Some(CodeDiff {
+        old_code: old_code.to_string(),
+        new_code: new_code.to_string(),
+    })
+}
+
+/// Change of a single metadata field (`name`, `description`, `image.url`, `image.ipfs_cid`,
+/// `tags.<tag>` or `fork_of`); `None` means that the field is not set
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct MetadataFieldChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl MetadataFieldChange {
+    pub fn print(&self) {
+        let removed = Style::new().red();
+        let added = Style::new().green();
+        if let Some(old) = &self.old {
+            println!(
+                "  {} {}: {}",
+                removed.apply_to("-").bold(),
+                removed.apply_to(&self.field),
+                removed.apply_to(old)
+            );
+        }
+        if let Some(new) = &self.new {
+            println!(
+                "  {} {}: {}",
+                added.apply_to("+").bold(),
+                added.apply_to(&self.field),
+                added.apply_to(new)
+            );
+        }
+    }
+}
+
+/// Field-by-field difference between two versions of the component metadata
+pub fn diff_metadata(
+    old_metadata: Option<&SocialDbComponentMetadata>,
+    new_metadata: Option<&SocialDbComponentMetadata>,
+) -> Vec<MetadataFieldChange> {
+    let mut old_fields = metadata_fields(old_metadata);
+    let new_fields = metadata_fields(new_metadata);
+
+    let mut changes = std::collections::BTreeMap::new();
+    for (key, new_value) in new_fields {
+        let old_value = old_fields.remove(&key);
+        if old_value.as_ref() != Some(&new_value) {
+            changes.insert(key, (old_value, Some(new_value)));
+        }
+    }
+    for (key, old_value) in old_fields {
+        changes.insert(key, (Some(old_value), None));
+    }
+    changes
+        .into_iter()
+        .map(|((_, field), (old, new))| MetadataFieldChange { field, old, new })
+        .collect()
+}
+
+/// Set metadata fields keyed by their position in the metadata (tags are sorted by name) and
+/// the field name
+fn metadata_fields(
+    metadata: Option<&SocialDbComponentMetadata>,
+) -> std::collections::BTreeMap<(u8, String), String> {
+    let mut fields = std::collections::BTreeMap::new();
+    let Some(metadata) = metadata else {
+        return fields;
+    };
+    let mut insert = |position: u8, field: &str, value: Option<&String>| {
+        if let Some(value) = value {
+            fields.insert((position, field.to_string()), value.clone());
+        }
+    };
+    insert(0, "name", metadata.name.as_ref());
+    insert(1, "description", metadata.description.as_ref());
+    if let Some(image) = &metadata.image {
+        insert(2, "image.url", image.url.as_ref());
+        insert(3, "image.ipfs_cid", image.ipfs_cid.as_ref());
+    }
+    for (tag, value) in metadata.tags.iter().flatten() {
+        // Tags are usually stored with empty values
+        insert(
+            4,
+            &format!("tags.{tag}"),
+            Some(&value.clone().unwrap_or_default()),
+        );
+    }
+    insert(5, "fork_of", metadata.fork_of.as_ref());
+    fields
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ComponentStatus {
+    /// The component exists only in the project source folder
+    Added,
+    Modified,
+    Unchanged,
+    /// The component exists only in SocialDB
+    RemoteOnly,
+}
+
+/// Difference between the deployed (`old`) and the local (`new`) version of a component
+#[derive(Debug, Clone)]
+pub struct ComponentDiff {
+    pub old_component: Option<crate::socialdb_types::SocialDbComponent>,
+    pub new_component: Option<crate::socialdb_types::SocialDbComponent>,
+    pub code_diff: Option<CodeDiff>,
+    pub metadata_changes: Vec<MetadataFieldChange>,
+}
+
+impl ComponentDiff {
+    pub fn new(
+        old_component: Option<&crate::socialdb_types::SocialDbComponent>,
+        new_component: Option<&crate::socialdb_types::SocialDbComponent>,
+        options: &DiffOptions,
+    ) -> Self {
+        let code_diff = match (old_component, new_component) {
+            (old_component, Some(new_component)) => diff_code(
+                old_component.map_or("", |old_component| old_component.code()),
+                new_component.code(),
+                options,
+            ),
+            (_, None) => None,
+        };
+        // Local components without metadata files keep the deployed metadata
+        let metadata_changes = match new_component.and_then(|component| component.metadata()) {
+            Some(new_metadata) => diff_metadata(
+                old_component.and_then(|component| component.metadata()),
+                Some(new_metadata),
+            ),
+            None => vec![],
+        };
+        Self {
+            old_component: old_component.cloned(),
+            new_component: new_component.cloned(),
+            code_diff,
+            metadata_changes,
+        }
+    }
+
+    pub fn has_metadata_changed(&self) -> bool {
+        !self.metadata_changes.is_empty()
+    }
+
+    pub fn status(&self) -> ComponentStatus {
+        match (&self.old_component, &self.new_component) {
+            (None, _) => ComponentStatus::Added,
+            (Some(_), None) => ComponentStatus::RemoteOnly,
+            (Some(_), Some(_)) if self.code_diff.is_some() || self.has_metadata_changed() => {
+                ComponentStatus::Modified
+            }
+            (Some(_), Some(_)) => ComponentStatus::Unchanged,
+        }
+    }
+
+    /// Prints the human-readable summary of the changes together with the code diff
+    pub fn print(&self, component_name: &str) {
+        match self.status() {
+            ComponentStatus::Added => {
+                println!("Found new component <{component_name}> to deploy");
+                return;
+            }
+            ComponentStatus::RemoteOnly => {
+                println!("Component <{component_name}> exists only in SocialDB");
+                return;
+            }
+            ComponentStatus::Modified | ComponentStatus::Unchanged => {}
+        }
+        match &self.code_diff {
+            Some(code_diff) => code_diff.print(),
+            None => println!("Code for component <{component_name}> has not changed"),
+        }
+        if self.has_metadata_changed() {
+            println!("Metadata for component <{component_name}> changed:");
+            for metadata_change in &self.metadata_changes {
+                metadata_change.print();
             }
+        } else {
+            println!("Metadata for component <{component_name}> has not changed");
         }
     }
-    Err(DiffCodeError)
 }
 
+/// Compares the local components with the deployed ones. The remote components that are not
+/// in `local_components` are reported as remote-only.
+pub fn diff_components(
+    local_components: &HashMap<
+        crate::socialdb_types::ComponentName,
+        crate::socialdb_types::SocialDbComponent,
+    >,
+    remote_components: &HashMap<
+        crate::socialdb_types::ComponentName,
+        crate::socialdb_types::SocialDbComponent,
+    >,
+    options: &DiffOptions,
+) -> std::collections::BTreeMap<crate::socialdb_types::ComponentName, ComponentDiff> {
+    local_components
+        .keys()
+        .chain(remote_components.keys())
+        .map(|component_name| {
+            (
+                component_name.clone(),
+                ComponentDiff::new(
+                    remote_components.get(component_name),
+                    local_components.get(component_name),
+                    options,
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Resolves the SocialDB contract account for the given network.
+///
+/// The lookup order is:
+/// 1. `BOS_SOCIAL_DB_CONTRACT_ACCOUNT_ID_<NETWORK>` environment variable (network name in upper case,
+///    non-alphanumeric characters replaced with `_`, e.g. `BOS_SOCIAL_DB_CONTRACT_ACCOUNT_ID_LOCALNET`);
+/// 2. `social_db_contract_account_id` key of the `[networks.<NETWORK>]` section in `bos.toml`
+///    (a manifest that fails to load is skipped with a warning);
+/// 3. `near_social_db_contract_account_id` key of the network connection in the near-cli config;
+/// 4. built-in defaults for `mainnet` and `testnet`.
+pub fn get_near_social_account_id(
+    network_config: &near_cli_rs::config::NetworkConfig,
+) -> color_eyre::eyre::Result<AccountId> {
+    let env_var_name = format!(
+        "{}{}",
+        crate::consts::NEAR_SOCIAL_ACCOUNT_ID_ENV_VAR_PREFIX,
+        network_config
+            .network_name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            })
+            .collect::<String>()
+    );
+    if let Ok(account_id) = std::env::var(&env_var_name) {
+        return account_id.trim().parse().wrap_err_with(|| {
+            format!("The <{env_var_name}> environment variable does not contain a valid account ID")
+        });
+    }
+    // The commands that do not work with components must not fail on a broken manifest
+    match crate::project::Project::load() {
+        Ok(project) => {
+            if let Some(account_id) = project
+                .network(&network_config.network_name)
+                .and_then(|network| network.social_db_contract_account_id.clone())
+            {
+                return Ok(account_id);
+            }
+        }
+        Err(err) => {
+            eprintln!("Warning: the SocialDB contract account is not read from bos.toml: {err:#}")
+        }
+    }
+    if let Some(account_id) = &network_config.near_social_db_contract_account_id {
+        return Ok(account_id.clone());
+    }
+    crate::consts::NEAR_SOCIAL_ACCOUNT_ID
+        .get(network_config.network_name.as_str())
+        .cloned()
+        .wrap_err_with(|| {
+            format!(
+                "The <{}> network does not have a near-social contract. Set <{env_var_name}> environment variable or add `social_db_contract_account_id` to the `[networks.{}]` section of bos.toml.",
+                network_config.network_name, network_config.network_name
+            )
+        })
+}
+
+/// Reads the local components. When `network_name` is given, `${config.<key>}` and `${env:<NAME>}`
+/// placeholders are rendered with the values for that network, otherwise they are masked (keeping
+/// the positions in the code), so that the code can still be parsed.
 pub fn get_local_components(
     account_id: Option<AccountId>,
+    network_name: Option<&str>,
 ) -> color_eyre::eyre::Result<HashMap<String, crate::socialdb_types::SocialDbComponent>> {
+    let project = crate::project::Project::load()?;
+    let variables = network_name.map(|network_name| project.variables(network_name));
+    let lockfile = crate::lockfile::Lockfile::load(&project.bos_file_path())?;
     let mut components = HashMap::new();
 
-    for component_filepath in glob("./src/**/*.jsx")?.filter_map(Result::ok) {
-        let component_name: crate::socialdb_types::ComponentName = component_filepath
-            .strip_prefix("src")?
-            .with_extension("")
-            .to_str()
-            .wrap_err_with(|| {
-                format!(
-                    "Component name cannot be presented as UTF-8: {}",
-                    component_filepath.display()
-                )
-            })?
-            .replace('/', ".");
+    for component_filepath in project.component_files()? {
+        let component_name = project.component_name(&component_filepath)?;
+
+        if components.contains_key(&component_name) {
+            color_eyre::eyre::bail!(
+                "There are several source files for component <{component_name}>, the last one is {}",
+                component_filepath.display()
+            );
+        }
 
         let code = std::fs::read_to_string(&component_filepath).wrap_err_with(|| {
             format!(
@@ -90,119 +533,195 @@ pub fn get_local_components(
                 component_filepath.display()
             )
         })?;
+        let code = crate::preprocessing::expand_includes(&component_filepath, &code)?;
+        let code = match &variables {
+            Some(_) => code,
+            None => crate::preprocessing::mask_variables(&code),
+        };
+        let code = if crate::typescript::is_typescript_file(&component_filepath) {
+            crate::typescript::strip_types(&component_filepath, &code).map_err(|err| {
+                // The position in the code with the included files is of little help
+                match find_source_syntax_error(&project, &component_filepath) {
+                    Some(syntax_error) => color_eyre::eyre::eyre!(
+                        "Failed to parse TypeScript component {}",
+                        syntax_error
+                    ),
+                    None => err,
+                }
+            })?
+        } else {
+            code
+        };
+        let code = if let Some(variables) = &variables {
+            crate::preprocessing::render_variables(&component_name, &code, variables)?
+        } else {
+            code
+        };
 
         let metadata_filepath = component_filepath.with_extension("metadata.json");
-        let components_original_sources = read_bos_file().unwrap();
-        if account_id.is_some() && components_original_sources.contains_key(&component_name) {
-            let source = components_original_sources.get(&component_name).unwrap();
-            let source_parts = source.split_once("/").unwrap();
-            if source_parts.0 != account_id.clone().unwrap().trim() {
-                let mut metadata_content_json = read_metadata_file(metadata_filepath.clone())?;
-                metadata_content_json.fork_of = Some(source.clone());
-                save_metadata_file(component_filepath, metadata_content_json).wrap_err_with(
-                    || {
-                        format!(
-                            "Failed to update component metadata {}",
-                            metadata_filepath.display()
-                        )
-                    },
-                )?;
+        let mut metadata: Option<SocialDbComponentMetadata> =
+            if let Ok(metadata_json) = std::fs::read_to_string(&metadata_filepath) {
+                Some(serde_json::from_str(&metadata_json).wrap_err_with(|| {
+                    format!(
+                        "Failed to parse component metadata from {}",
+                        metadata_filepath.display()
+                    )
+                })?)
+            } else {
+                None
+            };
+        // Components downloaded from other accounts are deployed as forks of the originals (but
+        // not back to the original account)
+        if let (Some(account_id), Some(locked)) =
+            (&account_id, lockfile.components.get(&component_name))
+        {
+            let fork_of = locked
+                .fork_of
+                .as_ref()
+                .filter(|fork_of| !fork_of.starts_with(&format!("{account_id}/widget/")));
+            if let Some(fork_of) = fork_of {
+                let metadata = metadata.get_or_insert_with(SocialDbComponentMetadata::default);
+                if metadata.fork_of.is_none() {
+                    metadata.fork_of = Some(fork_of.clone());
+                }
             }
         }
 
-        let metadata = if let Ok(metadata_json) = std::fs::read_to_string(&metadata_filepath) {
-            Some(serde_json::from_str(&metadata_json).wrap_err_with(|| {
-                format!(
-                    "Failed to parse component metadata from {}",
-                    metadata_filepath.display()
-                )
-            })?)
-        } else {
-            None
-        };
-
         components.insert(
             component_name,
             crate::socialdb_types::SocialDbComponent::CodeWithMetadata { code, metadata },
         );
     }
+
+    if let (true, Some(account_id)) = (project.manifest.inline_local_requires, &account_id) {
+        let components_code = components
+            .iter()
+            .map(|(component_name, component)| {
+                (component_name.clone(), component.code().to_string())
+            })
+            .collect();
+        let mut inlined_components_code =
+            crate::preprocessing::inline_local_requires(account_id, &components_code)?;
+        for (component_name, component) in components.iter_mut() {
+            if let crate::socialdb_types::SocialDbComponent::CodeWithMetadata { code, .. } =
+                component
+            {
+                if let Some(inlined_code) = inlined_components_code.remove(component_name) {
+                    *code = inlined_code;
+                }
+            }
+        }
+    }
     Ok(components)
 }
 
-pub fn save_metadata_file(
-    component_filepath: PathBuf,
-    metadata_content_json: SocialDbComponentMetadata,
-) -> color_eyre::eyre::Result<()> {
-    let metadata = serde_json::to_string_pretty(&metadata_content_json).wrap_err_with(|| {
-        format!(
-            "Failed to serialize component metadata for {}",
-            component_filepath.display()
-        )
-    })?;
-    let component_metadata_path = component_filepath.with_extension("metadata.json");
-    std::fs::write(&component_metadata_path, metadata.as_bytes()).wrap_err_with(|| {
-        format!(
-            "Failed to save component metadata into {}",
-            component_metadata_path.display()
-        )
-    })?;
-
-    Ok(())
+/// Names of the local components whose source or metadata files differ between `git_ref` and the working tree
+pub fn get_components_changed_since(
+    git_ref: &str,
+) -> color_eyre::eyre::Result<std::collections::HashSet<crate::socialdb_types::ComponentName>> {
+    let project = crate::project::Project::load()?;
+    let changed_files = crate::git::changed_files_since(&project.root, git_ref)?;
+    let mut changed_components = std::collections::HashSet::new();
+    for component_filepath in project.component_files()? {
+        // Git reports paths from the repository root with symlinks resolved
+        let is_changed = [
+            component_filepath.clone(),
+            component_filepath.with_extension("metadata.json"),
+        ]
+        .into_iter()
+        .filter_map(|filepath| filepath.canonicalize().ok())
+        .any(|filepath| changed_files.contains(&filepath));
+        if is_changed {
+            changed_components.insert(project.component_name(&component_filepath)?);
+        }
+    }
+    Ok(changed_components)
 }
 
-pub fn read_metadata_file(
-    metadata_filepath: PathBuf,
-) -> color_eyre::eyre::Result<SocialDbComponentMetadata> {
-    if metadata_filepath.is_file() {
-        let metadata_content = std::fs::read_to_string(&metadata_filepath).wrap_err_with(|| {
-            format!(
-                "Failed to parse component metadata from {}",
-                metadata_filepath.display()
-            )
-        })?;
-
-        let metadata_content_json: SocialDbComponentMetadata =
-            serde_json::from_str(&metadata_content).wrap_err_with(|| {
+/// Syntax errors of the components in the code they are deployed with. An error is reported at
+/// `<file>:<line>:<column>` of the component source file or of the included file that has it,
+/// and only at the line and column of the preprocessed code when none of the files has it on
+/// its own.
+pub fn find_syntax_errors(
+    components: &HashMap<
+        crate::socialdb_types::ComponentName,
+        crate::socialdb_types::SocialDbComponent,
+    >,
+) -> color_eyre::eyre::Result<Vec<String>> {
+    let project = crate::project::Project::load()?;
+    let mut syntax_errors = vec![];
+    for component_filepath in project.component_files()? {
+        let Some(component) = components.get(&project.component_name(&component_filepath)?) else {
+            continue;
+        };
+        let Err(code_error) = crate::jsx::parse(component.code()) else {
+            continue;
+        };
+        syntax_errors.push(
+            find_source_syntax_error(&project, &component_filepath).unwrap_or_else(|| {
                 format!(
-                    "Failed to parse component metadata from {}",
-                    metadata_filepath.display()
+                    "{}: line {}, column {} of the preprocessed code: {}",
+                    project.relative_path(&component_filepath).display(),
+                    code_error.line,
+                    code_error.column,
+                    code_error.message
                 )
-            })?;
-        Ok(metadata_content_json)
-    } else {
-        Ok(SocialDbComponentMetadata {
-            description: None,
-            image: None,
-            name: None,
-            tags: None,
-            fork_of: None,
-        })
+            }),
+        );
     }
+    Ok(syntax_errors)
 }
 
-pub fn read_bos_file() -> color_eyre::eyre::Result<HashMap<String, String>> {
-    let bos_path = std::path::PathBuf::from("./src/.bos");
-    if bos_path.is_file() {
-        Some(HashMap::<std::string::String, std::string::String>::new());
-    }
-    let file_content = std::fs::read_to_string(bos_path)?;
-
-    let result: HashMap<String, String> = file_content
-        .lines()
-        .map(|line| {
-            let parts: Vec<&str> = line.splitn(2, '=').collect();
-            if parts.len() != 2 {
-                Err(color_eyre::Report::msg(format!(
-                    "Failed to read .bos file. Invalid line format: {}",
-                    line
-                )))
+/// The first syntax error of the component source file or of the files it includes, each parsed
+/// on its own (with the placeholders masked), as `<file>:<line>:<column>: <message>`
+pub fn find_source_syntax_error(
+    project: &crate::project::Project,
+    component_filepath: &std::path::Path,
+) -> Option<String> {
+    std::iter::once(component_filepath.to_path_buf())
+        .chain(crate::preprocessing::included_files(component_filepath))
+        .find_map(|filepath| {
+            let code =
+                crate::preprocessing::mask_variables(&std::fs::read_to_string(&filepath).ok()?);
+            let syntax_error = if crate::typescript::is_typescript_file(&filepath) {
+                crate::typescript::check_syntax(&filepath, &code).err()
             } else {
-                Ok((parts[0].trim().to_string(), parts[1].trim().to_string()))
-            }
+                crate::jsx::parse(&code).err()
+            }?;
+            Some(if filepath == component_filepath {
+                format!(
+                    "{}:{syntax_error}",
+                    project.relative_path(&filepath).display()
+                )
+            } else {
+                format!(
+                    "{}:{syntax_error} (included from {})",
+                    project.relative_path(&filepath).display(),
+                    project.relative_path(component_filepath).display()
+                )
+            })
         })
-        .collect::<Result<_, _>>()?;
+}
 
-    Ok(result)
+/// Fails with the syntax errors of the components, so that broken code is never deployed
+pub fn ensure_valid_syntax(
+    components: &HashMap<
+        crate::socialdb_types::ComponentName,
+        crate::socialdb_types::SocialDbComponent,
+    >,
+) -> color_eyre::eyre::Result<()> {
+    let syntax_errors = find_syntax_errors(components)?;
+    if !syntax_errors.is_empty() {
+        color_eyre::eyre::bail!(
+            "The following components have syntax errors:\n{}\nFix them before deploying.",
+            syntax_errors
+                .iter()
+                .map(|syntax_error| format!(" * {syntax_error}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+    Ok(())
 }
 
 pub fn get_remote_components(
@@ -210,6 +729,7 @@ pub fn get_remote_components(
     component_name_list: Vec<&String>,
     near_social_account_id: &near_primitives::types::AccountId,
     account_id: &near_primitives::types::AccountId,
+    block_reference: &near_primitives::types::BlockReference,
 ) -> color_eyre::eyre::Result<
     HashMap<crate::socialdb_types::ComponentName, crate::socialdb_types::SocialDbComponent>,
 > {
@@ -228,6 +748,7 @@ pub fn get_remote_components(
                         near_social_account_id,
                         account_id,
                         components_name_batch,
+                        block_reference,
                     )
                     .await
                 })
@@ -246,6 +767,7 @@ async fn get_components(
     near_social_account_id: &near_primitives::types::AccountId,
     account_id: &near_primitives::types::AccountId,
     components_names_batch: &[&crate::socialdb_types::ComponentName],
+    block_reference: &near_primitives::types::BlockReference,
 ) -> color_eyre::Result<
     HashMap<crate::socialdb_types::ComponentName, crate::socialdb_types::SocialDbComponent>,
 > {
@@ -261,7 +783,7 @@ async fn get_components(
     match network_config
         .json_rpc_client()
         .call(near_jsonrpc_client::methods::query::RpcQueryRequest {
-            block_reference: near_primitives::types::Finality::Final.into(),
+            block_reference: block_reference.clone(),
             request: near_primitives::views::QueryRequest::CallFunction {
                 account_id: near_social_account_id.clone(),
                 method_name: "get".to_string(),
@@ -285,35 +807,213 @@ async fn get_components(
     }
 }
 
+/// Names of all the components of the account in SocialDB
+pub fn get_remote_component_names(
+    network_config: &near_cli_rs::config::NetworkConfig,
+    near_social_account_id: &near_primitives::types::AccountId,
+    account_id: &near_primitives::types::AccountId,
+) -> color_eyre::eyre::Result<Vec<crate::socialdb_types::ComponentName>> {
+    let input_args = serde_json::to_string(&crate::socialdb_types::SocialDbQuery {
+        keys: vec![format!("{account_id}/widget/*")],
+    })
+    .wrap_err("Internal error: could not serialize SocialDB input args")?;
+
+    let mut component_names = network_config
+        .json_rpc_client()
+        .blocking_call_view_function(
+            near_social_account_id,
+            "keys",
+            input_args.into_bytes(),
+            near_primitives::types::Finality::Final.into(),
+        )
+        .wrap_err("Failed to fetch the list of components from SocialDB")?
+        .parse_result_from_json::<crate::socialdb_types::SocialDbKeys>()
+        .wrap_err("SocialDB `keys` data response cannot be parsed")?
+        .accounts
+        .remove(account_id)
+        .map(|account_components| {
+            account_components
+                .components
+                .into_keys()
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    component_names.sort();
+    Ok(component_names)
+}
+
+/// Raw SocialDB data (`get` query) of the given components, including all their nested keys
+pub fn get_remote_components_data(
+    network_config: &near_cli_rs::config::NetworkConfig,
+    near_social_account_id: &near_primitives::types::AccountId,
+    account_id: &near_primitives::types::AccountId,
+    component_names: &[crate::socialdb_types::ComponentName],
+    block_reference: &near_primitives::types::BlockReference,
+) -> color_eyre::eyre::Result<serde_json::Value> {
+    let input_args = serde_json::to_string(&crate::socialdb_types::SocialDbQuery {
+        keys: component_names
+            .iter()
+            .map(|component_name| format!("{account_id}/widget/{component_name}/**"))
+            .collect(),
+    })
+    .wrap_err("Internal error: could not serialize SocialDB input args")?;
+
+    network_config
+        .json_rpc_client()
+        .blocking_call_view_function(
+            near_social_account_id,
+            "get",
+            input_args.into_bytes(),
+            block_reference.clone(),
+        )
+        .wrap_err("Failed to fetch the components from SocialDB")?
+        .parse_result_from_json()
+        .wrap_err("SocialDB `get` data response cannot be parsed")
+}
+
+/// Recursively merges the `source` object into the `target` object
+pub fn merge_json(target: &mut serde_json::Value, source: serde_json::Value) {
+    match (target, source) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(source)) => {
+            for (key, value) in source {
+                merge_json(target.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (target, source) => *target = source,
+    }
+}
+
+/// Returns the subset of `<account_id>/widget/<component_name>` paths that exist in SocialDB
+pub fn get_existing_remote_component_paths(
+    network_config: &near_cli_rs::config::NetworkConfig,
+    component_path_list: Vec<&String>,
+    near_social_account_id: &near_primitives::types::AccountId,
+) -> color_eyre::eyre::Result<std::collections::HashSet<String>> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    let chunk_size = 15;
+    let concurrency = 10;
+
+    runtime
+        .block_on(
+            futures::stream::iter(component_path_list.chunks(chunk_size))
+                .map(|component_paths_batch| async {
+                    get_existing_component_paths(
+                        network_config,
+                        near_social_account_id,
+                        component_paths_batch,
+                    )
+                    .await
+                })
+                .buffer_unordered(concurrency)
+                .collect::<Vec<Result<_, _>>>(),
+        )
+        .into_iter()
+        .try_fold(std::collections::HashSet::new(), |mut acc, x| {
+            acc.extend(x?);
+            Ok::<_, color_eyre::eyre::Error>(acc)
+        })
+}
+
+async fn get_existing_component_paths(
+    network_config: &near_cli_rs::config::NetworkConfig,
+    near_social_account_id: &near_primitives::types::AccountId,
+    component_paths_batch: &[&String],
+) -> color_eyre::Result<Vec<String>> {
+    let args = serde_json::to_string(&crate::socialdb_types::SocialDbQuery {
+        keys: component_paths_batch
+            .iter()
+            .map(|path| path.to_string())
+            .collect(),
+    })
+    .wrap_err("Internal error: could not serialize SocialDB input args")?
+    .into_bytes();
+
+    match network_config
+        .json_rpc_client()
+        .call(near_jsonrpc_client::methods::query::RpcQueryRequest {
+            block_reference: near_primitives::types::Finality::Final.into(),
+            request: near_primitives::views::QueryRequest::CallFunction {
+                account_id: near_social_account_id.clone(),
+                method_name: "keys".to_string(),
+                args: near_primitives::types::FunctionArgs::from(args),
+            },
+        })
+        .await
+        .wrap_err("Failed to query batch of component keys from Social DB")?
+        .kind
+    {
+        near_jsonrpc_primitives::types::query::QueryResponseKind::CallResult(call_result) => {
+            Ok(call_result
+                .parse_result_from_json::<crate::socialdb_types::SocialDbKeys>()
+                .wrap_err("ERROR: failed to parse Social DB response")?
+                .accounts
+                .into_iter()
+                .flat_map(|(account_id, account_components)| {
+                    account_components
+                        .components
+                        .into_keys()
+                        .map(move |component_name| format!("{account_id}/widget/{component_name}"))
+                })
+                .collect())
+        }
+        _ => unreachable!("ERROR: unexpected response type from JSON RPC client"),
+    }
+}
+
+/// Whether the component name contains glob characters
+pub fn is_component_name_pattern(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Keeps the components whose names match any of the patterns (component names or globs like `Dashboard.*`)
+pub fn filter_components_by_patterns<T>(
+    components: HashMap<crate::socialdb_types::ComponentName, T>,
+    patterns: &[String],
+) -> color_eyre::eyre::Result<HashMap<crate::socialdb_types::ComponentName, T>> {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| {
+            glob::Pattern::new(pattern)
+                .wrap_err_with(|| format!("Invalid component name pattern: {pattern}"))
+        })
+        .collect::<color_eyre::eyre::Result<Vec<_>>>()?;
+    if let Some(unmatched_pattern) = patterns.iter().find(|pattern| {
+        !components
+            .keys()
+            .any(|component_name| pattern.matches(component_name))
+    }) {
+        color_eyre::eyre::bail!("There are no components matching <{unmatched_pattern}>");
+    }
+    Ok(components
+        .into_iter()
+        .filter(|(component_name, _)| {
+            patterns
+                .iter()
+                .any(|pattern| pattern.matches(component_name))
+        })
+        .collect())
+}
+
 pub fn get_updated_components(
     local_components: HashMap<String, crate::socialdb_types::SocialDbComponent>,
     remote_components: &HashMap<
         crate::socialdb_types::ComponentName,
         crate::socialdb_types::SocialDbComponent,
     >,
+    options: &DiffOptions,
 ) -> HashMap<String, crate::socialdb_types::SocialDbComponent> {
     local_components
         .into_iter()
         .filter(|(component_name, new_component)| {
-            if let Some(old_component) = remote_components.get(component_name) {
-                let has_code_changed = crate::common::diff_code(old_component.code(), new_component.code()).is_err();
-                let has_metadata_changed = old_component.metadata() != new_component.metadata() && new_component.metadata().is_some();
-                if !has_code_changed {
-                    println!("Code for component <{component_name}> has not changed");
-                }
-                if has_metadata_changed {
-                    println!(
-                        "Metadata for component <{component_name}> changed:\n - old metadata: {:?}\n - new metadata: {:?}",
-                        old_component.metadata(), new_component.metadata()
-                    );
-                } else {
-                    println!("Metadata for component <{component_name}> has not changed");
-                }
-                has_code_changed || has_metadata_changed
-            } else {
-                println!("Found new component <{component_name}> to deploy");
-                true
-            }
+            let component_diff = ComponentDiff::new(
+                remote_components.get(component_name),
+                Some(new_component),
+                options,
+            );
+            component_diff.print(component_name);
+            component_diff.status() != ComponentStatus::Unchanged
         })
         .collect()
 }
@@ -357,3 +1057,111 @@ pub fn social_db_data_from_key(full_key: &str, data_to_set: &mut serde_json::Val
         *data_to_set = serde_json::json!({ full_key: data_to_set });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IGNORE_WHITESPACE: DiffOptions = DiffOptions {
+        ignore_whitespace: true,
+        ignore_line_endings: false,
+    };
+    const IGNORE_LINE_ENDINGS: DiffOptions = DiffOptions {
+        ignore_whitespace: false,
+        ignore_line_endings: true,
+    };
+
+    #[test]
+    fn normalizes_whitespace_outside_literals() {
+        assert_eq!(
+            IGNORE_WHITESPACE.normalize("if (a)  {\n\n    return  <div>{b}</div>;  \n}\n"),
+            "if (a) {\nreturn <div>{b}</div>;\n}"
+        );
+        // Comments are code, even with quotes in them
+        assert_eq!(
+            IGNORE_WHITESPACE.normalize("// it's   a comment\n  /* don't\n   */  x"),
+            "// it's a comment\n/* don't\n*/ x"
+        );
+    }
+
+    #[test]
+    fn keeps_whitespace_inside_literals() {
+        assert_eq!(
+            IGNORE_WHITESPACE.normalize("a  =  'x  y'  +  \"z  \\\"  w\";"),
+            "a = 'x  y' + \"z  \\\"  w\";"
+        );
+        assert_eq!(
+            IGNORE_WHITESPACE.normalize("t  =  `a  ${ {  k:  `b  c` }.k  }\n  d`;"),
+            "t = `a  ${ { k: `b  c` }.k }\n  d`;"
+        );
+    }
+
+    #[test]
+    fn normalizes_line_endings() {
+        assert_eq!(IGNORE_LINE_ENDINGS.normalize("a\r\n  b\r\n"), "a\n  b\n");
+        assert_eq!(IGNORE_WHITESPACE.normalize("a\r\n  b\r\n"), "a\nb");
+    }
+
+    #[test]
+    fn diff_code_ignores_only_selected_changes() {
+        let code = "return (\n  <div>\n    {props.text}\n  </div>\n);\n";
+        let reindented = "return (\n<div>\n  {props.text}\n</div>\n);";
+        let crlf = code.replace('\n', "\r\n");
+
+        assert!(diff_code(code, code, &DiffOptions::default()).is_none());
+        assert!(diff_code(code, reindented, &DiffOptions::default()).is_some());
+        assert!(diff_code(code, reindented, &IGNORE_WHITESPACE).is_none());
+        assert!(diff_code(code, reindented, &IGNORE_LINE_ENDINGS).is_some());
+
+        assert!(diff_code(code, &crlf, &DiffOptions::default()).is_some());
+        assert!(diff_code(code, &crlf, &IGNORE_LINE_ENDINGS).is_none());
+
+        // Text changes inside literals are changes with both options
+        let both = DiffOptions {
+            ignore_whitespace: true,
+            ignore_line_endings: true,
+        };
+        assert!(diff_code("const t = 'a b';", "const t = 'a  b';", &both).is_some());
+        assert!(diff_code("const t = 'a b';", "const  t  =  'a b';", &both).is_none());
+    }
+
+    #[test]
+    fn parses_placeholders_without_network() {
+        let project_dir =
+            std::env::temp_dir().join(format!("bos-cli-check-{}", std::process::id()));
+        std::fs::create_dir_all(project_dir.join("src")).unwrap();
+        std::fs::write(project_dir.join("bos.toml"), "").unwrap();
+        std::fs::write(
+            project_dir.join("src/Main.jsx"),
+            "return <a href={`${env:BOS_HOST}/x`}>Home</a>;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            project_dir.join("src/Typed.tsx"),
+            "const page: string = `${env:BOS_HOST}/${config.page}`;\nreturn <p>{page}</p>;\n",
+        )
+        .unwrap();
+        std::env::set_current_dir(&project_dir).unwrap();
+
+        let components = get_local_components(None, None).unwrap();
+        let syntax_errors = find_syntax_errors(&components).unwrap();
+        std::fs::remove_dir_all(&project_dir).unwrap();
+        assert_eq!(components.len(), 2);
+        assert!(syntax_errors.is_empty(), "{syntax_errors:?}");
+    }
+
+    #[test]
+    fn unified_patch_keeps_exact_contents() {
+        let patch = unified_patch("\nreturn 1;\n", "\nreturn 2;", "a/A.jsx", "b/A.jsx");
+        assert_eq!(
+            patch,
+            "--- a/A.jsx\n+++ b/A.jsx\n@@ -1,2 +1,2 @@\n \n-return 1;\n+return 2;\n\\ No newline at end of file\n"
+        );
+
+        let patch = unified_patch("", "return 1;", "/dev/null", "b/A.jsx");
+        assert_eq!(
+            patch,
+            "--- /dev/null\n+++ b/A.jsx\n@@ -0,0 +1 @@\n+return 1;\n\\ No newline at end of file\n"
+        );
+    }
+}
@@ -0,0 +1,68 @@
+#[derive(Debug, Clone, interactive_clap::InteractiveClap)]
+#[interactive_clap(input_context = near_cli_rs::GlobalContext)]
+#[interactive_clap(output_context = CheckCmdContext)]
+#[interactive_clap(skip_default_from_cli)]
+pub struct CheckCmd {
+    #[interactive_clap(long)]
+    #[interactive_clap(skip_default_input_arg)]
+    /// Render the `${config.<key>}` placeholders with the values for the network, as deploy does
+    network: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct CheckCmdContext;
+
+impl CheckCmdContext {
+    pub fn from_previous_context(
+        _previous_context: near_cli_rs::GlobalContext,
+        scope: &<CheckCmd as interactive_clap::ToInteractiveClapContextScope>::InteractiveClapContextScope,
+    ) -> color_eyre::eyre::Result<Self> {
+        let local_components = crate::common::get_local_components(None, scope.network.as_deref())?;
+        if local_components.is_empty() {
+            println!("There are no components in the project source folder. Goodbye.");
+            return Ok(Self);
+        }
+
+        let syntax_errors = crate::common::find_syntax_errors(&local_components)?;
+        if syntax_errors.is_empty() {
+            println!(
+                "All <{}> components were parsed successfully.",
+                local_components.len()
+            );
+            return Ok(Self);
+        }
+        for syntax_error in &syntax_errors {
+            println!("{syntax_error}");
+        }
+        color_eyre::eyre::bail!(
+            "<{}> of <{}> components have syntax errors",
+            syntax_errors.len(),
+            local_components.len()
+        );
+    }
+}
+
+impl interactive_clap::FromCli for CheckCmd {
+    type FromCliContext = near_cli_rs::GlobalContext;
+    type FromCliError = color_eyre::eyre::Error;
+
+    fn from_cli(
+        optional_clap_variant: Option<<Self as interactive_clap::ToCli>::CliVariant>,
+        context: Self::FromCliContext,
+    ) -> interactive_clap::ResultFromCli<
+        <Self as interactive_clap::ToCli>::CliVariant,
+        Self::FromCliError,
+    >
+    where
+        Self: Sized + interactive_clap::ToCli,
+    {
+        let clap_variant = optional_clap_variant.unwrap_or_default();
+        let network = clap_variant.network.clone();
+
+        let new_context_scope = InteractiveClapContextScopeForCheckCmd { network };
+        match CheckCmdContext::from_previous_context(context, &new_context_scope) {
+            Ok(_) => interactive_clap::ResultFromCli::Ok(clap_variant),
+            Err(err) => interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+        }
+    }
+}
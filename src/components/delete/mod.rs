@@ -34,9 +34,14 @@ impl DeleteCmd {
     pub fn input_account_id(
         context: &near_cli_rs::GlobalContext,
     ) -> color_eyre::eyre::Result<Option<near_cli_rs::types::account_id::AccountId>> {
-        near_cli_rs::common::input_signer_account_id_from_used_account_list(
-            &context.config.credentials_home_dir,
+        crate::project::input_account_id(
             "Which account do you want to delete the components from?",
+            || {
+                near_cli_rs::common::input_signer_account_id_from_used_account_list(
+                    &context.config.credentials_home_dir,
+                    "Which account do you want to delete the components from?",
+                )
+            },
         )
     }
 }
@@ -1,4 +1,4 @@
-use color_eyre::eyre::{ContextCompat, WrapErr};
+use color_eyre::eyre::WrapErr;
 use inquire::{CustomType, Select};
 use near_cli_rs::common::{CallResultExt, JsonRpcClientExt};
 
@@ -44,8 +44,7 @@ impl From<SignerContext> for near_cli_rs::commands::ActionContext {
             let signer_id = item.signer_account_id.clone();
 
             move |network_config| {
-                let near_social_account_id = crate::consts::NEAR_SOCIAL_ACCOUNT_ID.get(network_config.network_name.as_str())
-                    .wrap_err_with(|| format!("The <{}> network does not have a near-social contract.", network_config.network_name))?;
+                let near_social_account_id = &crate::common::get_near_social_account_id(network_config)?;
 
                 let keys_components_to_remove = if components.is_empty() {
                     vec![format!("{account_id}/widget/**")]
@@ -134,7 +133,12 @@ impl Signer {
         loop {
             let signer_account_id: near_cli_rs::types::account_id::AccountId =
                 CustomType::new("What is the signer account ID?")
-                    .with_default(context.account_id.clone())
+                    .with_default(
+                        crate::project::Project::load()?
+                            .signer_account_id(context.account_id.as_ref())
+                            .map(Into::into)
+                            .unwrap_or_else(|| context.account_id.clone()),
+                    )
                     .prompt()?;
             if !near_cli_rs::common::is_account_exist(
                 &context.global_context.config.network_connection,
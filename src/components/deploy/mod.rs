@@ -1,20 +1,297 @@
-use color_eyre::eyre::ContextCompat;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use color_eyre::eyre::{ContextCompat, WrapErr};
 use inquire::Select;
+use near_cli_rs::common::JsonRpcClientExt;
 
 mod sign_as;
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct TransactionFunctionArgs {
-    pub data: crate::socialdb_types::SocialDb,
+/// Arguments of the SocialDB `set` function call and the deposit required to store `new_data`
+/// in place of `remote_data`
+pub fn get_set_args_and_deposit(
+    network_config: &near_cli_rs::config::NetworkConfig,
+    near_social_account_id: &near_primitives::types::AccountId,
+    account_id: &near_primitives::types::AccountId,
+    new_data: serde_json::Value,
+    remote_data: &serde_json::Value,
+) -> color_eyre::eyre::Result<(Vec<u8>, near_cli_rs::common::NearBalance)> {
+    let deposit = tokio::runtime::Runtime::new().unwrap().block_on(
+        near_socialdb_client::required_deposit(
+            &network_config.json_rpc_client(),
+            near_social_account_id,
+            account_id,
+            &new_data,
+            Some(remote_data),
+        ),
+    )?;
+    let args = serde_json::to_string(&serde_json::json!({
+        "data": new_data,
+    }))?
+    .into_bytes();
+    Ok((args, deposit))
+}
+
+pub fn set_action(
+    args: Vec<u8>,
+    deposit: near_cli_rs::common::NearBalance,
+) -> near_primitives::transaction::Action {
+    near_primitives::transaction::Action::FunctionCall(
+        near_primitives::transaction::FunctionCallAction {
+            method_name: "set".to_string(),
+            args,
+            gas: near_cli_rs::common::NearGas::from_tgas(300).as_gas(),
+            deposit: deposit.to_yoctonear(),
+        },
+    )
+}
+
+/// Recalculates the deposit of the `set` function call for the access key that signs the transaction
+pub fn update_deposit_before_signing(
+    signer_account_id: near_primitives::types::AccountId,
+    account_id: near_primitives::types::AccountId,
+) -> near_cli_rs::commands::OnBeforeSigningCallback {
+    std::sync::Arc::new(move |prepopulated_unsigned_transaction, network_config| {
+        let json_rpc_client = network_config.json_rpc_client();
+        if let near_primitives::transaction::Action::FunctionCall(action) =
+            &mut prepopulated_unsigned_transaction.actions[0]
+        {
+            action.deposit = tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(near_socialdb_client::get_deposit(
+                    &json_rpc_client,
+                    &signer_account_id,
+                    &prepopulated_unsigned_transaction.public_key,
+                    &account_id,
+                    "widget",
+                    &prepopulated_unsigned_transaction.receiver_id,
+                    near_cli_rs::common::NearBalance::from_yoctonear(action.deposit),
+                ))?
+                .to_yoctonear();
+            Ok(())
+        } else {
+            color_eyre::eyre::bail!("Unexpected action to change components",);
+        }
+    })
+}
+
+/// Records the deployed components as the base versions in the lockfile (`.bos`) and removes
+/// the entries of the removed ones
+pub fn update_lockfile(
+    network_config: &near_cli_rs::config::NetworkConfig,
+    transaction_info: &near_primitives::views::FinalExecutionOutcomeView,
+    account_id: &near_primitives::types::AccountId,
+    deployed_component_names: &[&String],
+    removed_component_names: &[&String],
+) -> color_eyre::eyre::Result<()> {
+    // The `set` function call is executed in the first receipt of the transaction
+    let receipt_block_hash = transaction_info
+        .receipts_outcome
+        .first()
+        .wrap_err("Internal error: the deploy transaction has no receipts")?
+        .block_hash;
+    let block_height = network_config
+        .json_rpc_client()
+        .blocking_call(near_jsonrpc_client::methods::block::RpcBlockRequest {
+            block_reference: near_primitives::types::BlockReference::BlockId(
+                near_primitives::types::BlockId::Hash(receipt_block_hash),
+            ),
+        })
+        .wrap_err("Failed to get the block of the deploy transaction")?
+        .header
+        .height;
+
+    let project = crate::project::Project::load()?;
+    let src_dir = project.src_dir();
+    let mut component_paths = HashMap::new();
+    for component_filepath in project.component_files()? {
+        component_paths.insert(
+            project.component_name(&component_filepath)?,
+            component_filepath.strip_prefix(&src_dir)?.to_path_buf(),
+        );
+    }
+
+    let lockfile_path = project.bos_file_path();
+    let mut lockfile = crate::lockfile::Lockfile::load(&lockfile_path)?;
+    lockfile.record_deployed(
+        account_id,
+        block_height,
+        deployed_component_names
+            .iter()
+            .filter_map(|component_name| {
+                component_paths
+                    .get(*component_name)
+                    .map(|path| ((*component_name).clone(), path.clone()))
+            }),
+    );
+    for component_name in removed_component_names {
+        lockfile.components.remove(*component_name);
+    }
+    lockfile.save(&lockfile_path)
+}
+
+/// Fails with a conflict report if any of the components changed in SocialDB after its base
+/// version recorded in the lockfile (`.bos`)
+pub fn ensure_no_remote_changes(
+    network_config: &near_cli_rs::config::NetworkConfig,
+    near_social_account_id: &near_primitives::types::AccountId,
+    account_id: &near_primitives::types::AccountId,
+    component_names: &[&crate::socialdb_types::ComponentName],
+) -> color_eyre::eyre::Result<()> {
+    let lockfile =
+        crate::lockfile::Lockfile::load(&crate::project::Project::load()?.bos_file_path())?;
+    let base_block_heights = component_names
+        .iter()
+        .filter_map(|component_name| {
+            let locked = lockfile.components.get(*component_name)?;
+            Some((*component_name, locked.block_height(account_id)?))
+        })
+        .collect::<BTreeMap<_, _>>();
+    if base_block_heights.is_empty() {
+        return Ok(());
+    }
+
+    let current_block_heights = super::download::get_component_block_heights(
+        network_config,
+        near_social_account_id,
+        base_block_heights
+            .keys()
+            .map(|component_name| format!("{account_id}/widget/{component_name}"))
+            .collect(),
+        &near_primitives::types::Finality::Final.into(),
+    )?
+    .accounts
+    .remove(account_id)
+    .map(|account_components| account_components.components)
+    .unwrap_or_default();
+
+    let conflicts = base_block_heights
+        .into_iter()
+        .filter_map(
+            |(component_name, base_block_height)| match current_block_heights.get(component_name) {
+                Some(block_height) if *block_height == base_block_height => None,
+                Some(block_height) => Some(format!(
+                    " * {component_name}: changed at block height <{block_height}>, the base version is at <{base_block_height}>"
+                )),
+                None => Some(format!(
+                    " * {component_name}: removed, the base version is at <{base_block_height}>"
+                )),
+            },
+        )
+        .collect::<Vec<_>>();
+    if !conflicts.is_empty() {
+        color_eyre::eyre::bail!(
+            "The following components were changed on <{account_id}> after the versions recorded in .bos:\n{}\nDownload the components to merge the remote changes, or deploy with --force to overwrite them.",
+            conflicts.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// Transactions of a deploy that does not fit into a single one.
+///
+/// The components that differ from SocialDB are split into batches once, and all the batches are
+/// signed and sent one after another in the same run of the command.
+#[derive(Debug, Default)]
+pub struct DeployBatches {
+    /// SocialDB data of the batches that are not sent yet, with the remote data of the same keys
+    /// (the deposit is calculated right before sending, when the previous batches are stored)
+    pub pending: VecDeque<(serde_json::Value, serde_json::Value)>,
+    pub batches_count: usize,
+    /// Number of the batch being sent (starting from 1), or 0 before the components are split
+    pub current_batch: usize,
+    /// Nonce of the last signed transaction: the next batch must use a greater one, even if the
+    /// RPC node has not seen the previous transaction yet
+    pub last_nonce: Option<near_primitives::types::Nonce>,
+}
+
+/// Size and gas limits of a single deploy transaction
+#[derive(Debug, Clone, Copy)]
+pub struct BatchLimits {
+    /// Maximum size of the written SocialDB data (in bytes)
+    pub max_bytes: usize,
+    /// Maximum number of the written components
+    pub max_components: usize,
+}
+
+/// Splits the components (in the order of their names) into batches that fit the size and
+/// gas limits of a single transaction. `first_batch_reserved` is the size (in bytes) and the
+/// number of components of the data that is written in the first batch in addition to the
+/// components (the removed components). A component that exceeds the size limit on its own
+/// gets a batch of its own.
+pub fn split_into_batches(
+    components: HashMap<
+        crate::socialdb_types::ComponentName,
+        crate::socialdb_types::SocialDbComponent,
+    >,
+    first_batch_reserved: (usize, usize),
+    limits: BatchLimits,
+) -> color_eyre::eyre::Result<
+    Vec<HashMap<crate::socialdb_types::ComponentName, crate::socialdb_types::SocialDbComponent>>,
+> {
+    let mut batches = vec![];
+    let mut batch = HashMap::new();
+    let (mut batch_bytes, mut batch_components) = first_batch_reserved;
+    for (component_name, component) in components.into_iter().collect::<BTreeMap<_, _>>() {
+        // `"<name>":<component>,` as it is written in the `set` arguments
+        let component_bytes = serde_json::to_string(&component_name)?.len()
+            + serde_json::to_string(&component)?.len()
+            + 2;
+        if batch_components > 0
+            && (batch_bytes + component_bytes > limits.max_bytes
+                || batch_components >= limits.max_components)
+        {
+            // The first batch may only contain the reserved data
+            batches.push(std::mem::take(&mut batch));
+            batch_bytes = 0;
+            batch_components = 0;
+        }
+        batch_bytes += component_bytes;
+        batch_components += 1;
+        batch.insert(component_name, component);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    Ok(batches)
 }
 
 #[derive(Debug, Clone, interactive_clap::InteractiveClap)]
 #[interactive_clap(input_context = near_cli_rs::GlobalContext)]
 #[interactive_clap(output_context = DeployCmdContext)]
+#[interactive_clap(skip_default_from_cli)]
 pub struct DeployCmd {
     #[interactive_clap(skip_default_input_arg)]
     /// Which account do you want to deploy the components to?
     deploy_to_account_id: near_cli_rs::types::account_id::AccountId,
+    #[interactive_clap(long)]
+    /// Print the transaction arguments and the deposit without signing and sending the transaction
+    dry_run: bool,
+    #[interactive_clap(long)]
+    #[interactive_clap(skip_default_input_arg)]
+    /// Deploy only the components matching the comma-separated names or glob patterns (e.g. "Dashboard.*")
+    only: Option<near_cli_rs::types::vec_string::VecString>,
+    #[interactive_clap(long)]
+    #[interactive_clap(skip_default_input_arg)]
+    /// Deploy only the components whose source or metadata files changed since the git ref
+    since: Option<String>,
+    #[interactive_clap(long)]
+    /// Remove the components that exist in SocialDB, but not in the project source folder
+    prune: bool,
+    #[interactive_clap(long)]
+    /// Remove the pruned components without a confirmation (required when not run in a terminal)
+    yes: bool,
+    #[interactive_clap(long)]
+    /// Do not consider whitespace-only changes (e.g. reindentation) as changes
+    ignore_whitespace: bool,
+    #[interactive_clap(long)]
+    /// Do not consider CRLF vs LF line endings as changes
+    ignore_line_endings: bool,
+    #[interactive_clap(long)]
+    /// Overwrite the components that were changed in SocialDB after the versions recorded in .bos
+    force: bool,
+    #[interactive_clap(long)]
+    /// Do not record the deployed components as the base versions in .bos (e.g. on development accounts)
+    skip_lockfile_update: bool,
     #[interactive_clap(named_arg)]
     /// Specify signer account ID
     sign_as: self::sign_as::Signer,
@@ -24,6 +301,16 @@ pub struct DeployCmd {
 pub struct DeployCmdContext {
     pub global_context: near_cli_rs::GlobalContext,
     pub deploy_to_account_id: near_cli_rs::types::account_id::AccountId,
+    pub dry_run: bool,
+    pub only: Vec<String>,
+    pub since: Option<String>,
+    pub prune: bool,
+    pub yes: bool,
+    pub diff_options: crate::common::DiffOptions,
+    pub force: bool,
+    pub skip_lockfile_update: bool,
+    /// Select the components to deploy among the changed ones (only in the interactive mode)
+    pub select_components: bool,
 }
 
 impl DeployCmdContext {
@@ -34,15 +321,137 @@ impl DeployCmdContext {
         Ok(Self {
             global_context: previous_context,
             deploy_to_account_id: scope.deploy_to_account_id.clone(),
+            dry_run: scope.dry_run,
+            only: scope.only.clone().map(Into::into).unwrap_or_default(),
+            since: scope.since.clone(),
+            prune: scope.prune,
+            yes: scope.yes,
+            diff_options: crate::common::DiffOptions {
+                ignore_whitespace: scope.ignore_whitespace,
+                ignore_line_endings: scope.ignore_line_endings,
+            },
+            force: scope.force,
+            skip_lockfile_update: scope.skip_lockfile_update,
+            select_components: false,
         })
     }
 }
 
+impl interactive_clap::FromCli for DeployCmd {
+    type FromCliContext = near_cli_rs::GlobalContext;
+    type FromCliError = color_eyre::eyre::Error;
+
+    fn from_cli(
+        optional_clap_variant: Option<<Self as interactive_clap::ToCli>::CliVariant>,
+        context: Self::FromCliContext,
+    ) -> interactive_clap::ResultFromCli<
+        <Self as interactive_clap::ToCli>::CliVariant,
+        Self::FromCliError,
+    >
+    where
+        Self: Sized + interactive_clap::ToCli,
+    {
+        let mut clap_variant = optional_clap_variant.unwrap_or_default();
+
+        // The components are only offered for selection when the command is built interactively
+        let is_interactive = clap_variant.deploy_to_account_id.is_none();
+        if clap_variant.deploy_to_account_id.is_none() {
+            clap_variant.deploy_to_account_id = match Self::input_deploy_to_account_id(&context) {
+                Ok(Some(deploy_to_account_id)) => Some(deploy_to_account_id),
+                Ok(None) => return interactive_clap::ResultFromCli::Cancel(Some(clap_variant)),
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+        }
+        let deploy_to_account_id = clap_variant
+            .deploy_to_account_id
+            .clone()
+            .expect("Unexpected error");
+        let dry_run = clap_variant.dry_run;
+        let select_components = if clap_variant.only.is_none() && is_interactive {
+            match Self::input_select_components(&context) {
+                Ok(select_components) => select_components,
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            }
+        } else {
+            false
+        };
+        let only = clap_variant.only.clone();
+        let since = clap_variant.since.clone();
+        let prune = clap_variant.prune;
+        let yes = clap_variant.yes;
+        let ignore_whitespace = clap_variant.ignore_whitespace;
+        let ignore_line_endings = clap_variant.ignore_line_endings;
+        let force = clap_variant.force;
+        let skip_lockfile_update = clap_variant.skip_lockfile_update;
+
+        let new_context_scope = InteractiveClapContextScopeForDeployCmd {
+            deploy_to_account_id,
+            dry_run,
+            only,
+            since,
+            prune,
+            yes,
+            ignore_whitespace,
+            ignore_line_endings,
+            force,
+            skip_lockfile_update,
+        };
+        let mut output_context =
+            match DeployCmdContext::from_previous_context(context, &new_context_scope) {
+                Ok(new_context) => new_context,
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+        output_context.select_components = select_components;
+
+        let optional_sign_as = clap_variant
+            .sign_as
+            .take()
+            .map(|ClapNamedArgSignerForDeployCmd::SignAs(cli_sign_as)| cli_sign_as);
+        match self::sign_as::Signer::from_cli(optional_sign_as, output_context) {
+            interactive_clap::ResultFromCli::Ok(cli_sign_as) => {
+                clap_variant.sign_as = Some(ClapNamedArgSignerForDeployCmd::SignAs(cli_sign_as));
+                interactive_clap::ResultFromCli::Ok(clap_variant)
+            }
+            interactive_clap::ResultFromCli::Cancel(optional_cli_sign_as) => {
+                clap_variant.sign_as =
+                    optional_cli_sign_as.map(ClapNamedArgSignerForDeployCmd::SignAs);
+                interactive_clap::ResultFromCli::Cancel(Some(clap_variant))
+            }
+            interactive_clap::ResultFromCli::Back => interactive_clap::ResultFromCli::Back,
+            interactive_clap::ResultFromCli::Err(optional_cli_sign_as, err) => {
+                clap_variant.sign_as =
+                    optional_cli_sign_as.map(ClapNamedArgSignerForDeployCmd::SignAs);
+                interactive_clap::ResultFromCli::Err(Some(clap_variant), err)
+            }
+        }
+    }
+}
+
 impl DeployCmd {
+    /// Asks whether all the changed components are deployed, or a part of them is selected once
+    /// the changed components are known (after the network is selected)
+    fn input_select_components(
+        _context: &near_cli_rs::GlobalContext,
+    ) -> color_eyre::eyre::Result<bool> {
+        #[derive(strum_macros::Display)]
+        enum ConfirmOptions {
+            #[strum(to_string = "Yes, I want to deploy all the changed components.")]
+            Yes,
+            #[strum(to_string = "No, I want to select the components to deploy.")]
+            No,
+        }
+        let select_choose_input = Select::new(
+            "Do you want to deploy all the changed components?",
+            vec![ConfirmOptions::Yes, ConfirmOptions::No],
+        )
+        .prompt()?;
+        Ok(matches!(select_choose_input, ConfirmOptions::No))
+    }
+
     fn input_deploy_to_account_id(
         context: &near_cli_rs::GlobalContext,
     ) -> color_eyre::eyre::Result<Option<near_cli_rs::types::account_id::AccountId>> {
-        let components = crate::common::get_local_components(None)?;
+        let components = crate::common::get_local_components(None, None)?;
         println!(
             "\nThere are <{}> components in the current folder ready for deployment:",
             components.len()
@@ -51,12 +460,16 @@ impl DeployCmd {
             println!(" * {component}")
         }
         loop {
-            let deploy_to_account_id =
-                near_cli_rs::common::input_signer_account_id_from_used_account_list(
-                    &context.config.credentials_home_dir,
-                    "Which account do you want to deploy the components to?",
-                )?
-                .wrap_err("Internal error!")?;
+            let deploy_to_account_id = crate::project::input_account_id(
+                "Which account do you want to deploy the components to?",
+                || {
+                    near_cli_rs::common::input_signer_account_id_from_used_account_list(
+                        &context.config.credentials_home_dir,
+                        "Which account do you want to deploy the components to?",
+                    )
+                },
+            )?
+            .wrap_err("Internal error!")?;
             if !near_cli_rs::common::is_account_exist(
                 &context.config.network_connection,
                 deploy_to_account_id.clone().into(),
@@ -86,3 +499,84 @@ impl DeployCmd {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::socialdb_types::SocialDbComponent;
+
+    fn components(sizes: &[(&str, usize)]) -> HashMap<String, SocialDbComponent> {
+        sizes
+            .iter()
+            .map(|(name, size)| (name.to_string(), SocialDbComponent::Code("x".repeat(*size))))
+            .collect()
+    }
+
+    fn names(batches: &[HashMap<String, SocialDbComponent>]) -> Vec<Vec<String>> {
+        batches
+            .iter()
+            .map(|batch| {
+                let mut names = batch.keys().cloned().collect::<Vec<_>>();
+                names.sort();
+                names
+            })
+            .collect()
+    }
+
+    const LIMITS: BatchLimits = BatchLimits {
+        max_bytes: 100,
+        max_components: 10,
+    };
+
+    #[test]
+    fn splits_by_size() {
+        // Each component takes `"A":"x…x",` = 30 bytes
+        let batches = split_into_batches(
+            components(&[("A", 23), ("B", 23), ("C", 23), ("D", 23)]),
+            (0, 0),
+            LIMITS,
+        )
+        .unwrap();
+        assert_eq!(names(&batches), [vec!["A", "B", "C"], vec!["D"]]);
+    }
+
+    #[test]
+    fn puts_oversized_component_into_own_batch() {
+        let batches = split_into_batches(
+            components(&[("A", 10), ("B", 500), ("C", 10)]),
+            (0, 0),
+            LIMITS,
+        )
+        .unwrap();
+        assert_eq!(names(&batches), [vec!["A"], vec!["B"], vec!["C"]]);
+    }
+
+    #[test]
+    fn splits_by_component_count() {
+        let batches = split_into_batches(
+            components(&[("A", 1), ("B", 1), ("C", 1), ("D", 1), ("E", 1)]),
+            (0, 0),
+            BatchLimits {
+                max_components: 2,
+                ..LIMITS
+            },
+        )
+        .unwrap();
+        assert_eq!(names(&batches), [vec!["A", "B"], vec!["C", "D"], vec!["E"]]);
+    }
+
+    #[test]
+    fn counts_reserved_data_in_first_batch() {
+        let batches = split_into_batches(
+            components(&[("A", 23), ("B", 23), ("C", 23)]),
+            (50, 1),
+            LIMITS,
+        )
+        .unwrap();
+        assert_eq!(names(&batches), [vec!["A"], vec!["B", "C"]]);
+
+        // The reserved data alone does not leave room for any component
+        let batches = split_into_batches(components(&[("A", 23)]), (90, 1), LIMITS).unwrap();
+        assert_eq!(names(&batches), [vec![], vec!["A"]]);
+    }
+}
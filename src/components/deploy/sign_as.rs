@@ -1,12 +1,14 @@
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::io::IsTerminal;
+use std::sync::{Arc, Mutex};
 
 use color_eyre::eyre::{ContextCompat, WrapErr};
-use inquire::{CustomType, Select};
+use inquire::{CustomType, MultiSelect, Select};
 
 #[derive(Debug, Clone, interactive_clap::InteractiveClap)]
 #[interactive_clap(input_context = super::DeployCmdContext)]
 #[interactive_clap(output_context = SignerContext)]
+#[interactive_clap(skip_default_from_cli)]
 pub struct Signer {
     #[interactive_clap(skip_default_input_arg)]
     /// What is the signer account ID?
@@ -21,6 +23,16 @@ pub struct SignerContext {
     global_context: near_cli_rs::GlobalContext,
     deploy_to_account_id: near_primitives::types::AccountId,
     signer_account_id: near_primitives::types::AccountId,
+    dry_run: bool,
+    only: Vec<String>,
+    since: Option<String>,
+    prune: bool,
+    yes: bool,
+    diff_options: crate::common::DiffOptions,
+    force: bool,
+    skip_lockfile_update: bool,
+    select_components: bool,
+    batches: Arc<Mutex<super::DeployBatches>>,
 }
 
 impl SignerContext {
@@ -32,6 +44,16 @@ impl SignerContext {
             global_context: previous_context.global_context,
             deploy_to_account_id: previous_context.deploy_to_account_id.into(),
             signer_account_id: scope.signer_account_id.clone().into(),
+            dry_run: previous_context.dry_run,
+            only: previous_context.only,
+            since: previous_context.since,
+            prune: previous_context.prune,
+            yes: previous_context.yes,
+            diff_options: previous_context.diff_options,
+            force: previous_context.force,
+            skip_lockfile_update: previous_context.skip_lockfile_update,
+            select_components: previous_context.select_components,
+            batches: Arc::new(Mutex::new(super::DeployBatches::default())),
         })
     }
 }
@@ -40,117 +62,264 @@ impl From<SignerContext> for near_cli_rs::commands::ActionContext {
     fn from(item: SignerContext) -> Self {
         let deploy_to_account_id = item.deploy_to_account_id.clone();
         let signer_id = item.signer_account_id.clone();
+        let dry_run = item.dry_run;
+        let only = item.only.clone();
+        let since = item.since.clone();
+        let prune = item.prune;
+        let yes = item.yes;
+        let diff_options = item.diff_options;
+        let force = item.force;
+        let select_components = item.select_components;
+        let batches = item.batches.clone();
 
         let on_after_getting_network_callback: near_cli_rs::commands::OnAfterGettingNetworkCallback = Arc::new({
             move |network_config| {
-                let near_social_account_id = crate::consts::NEAR_SOCIAL_ACCOUNT_ID.get(network_config.network_name.as_str())
-                    .wrap_err_with(|| format!("The <{}> network does not have a near-social contract.", network_config.network_name))?;
+                let near_social_account_id = &crate::common::get_near_social_account_id(network_config)?;
                 let mut prepopulated_transaction = near_cli_rs::commands::PrepopulatedTransaction {
                     signer_id: signer_id.clone(),
                     receiver_id: near_social_account_id.clone(),
                     actions: vec![],
                 };
-                let local_components = crate::common::get_local_components(Some(deploy_to_account_id.clone()))?;
-                if local_components.is_empty() {
-                    println!("There are no components in the current ./src folder. Goodbye.");
+                // The components were already split into batches by the first transaction of this deploy
+                {
+                    let mut batches = batches.lock().unwrap();
+                    if batches.current_batch > 0 {
+                        let (new_data, remote_data) = batches
+                            .pending
+                            .pop_front()
+                            .wrap_err("Internal error: there are no batches of components left to deploy")?;
+                        batches.current_batch += 1;
+                        println!("\nSending batch {}/{}.", batches.current_batch, batches.batches_count);
+                        let (args, deposit) = super::get_set_args_and_deposit(
+                            network_config,
+                            near_social_account_id,
+                            &deploy_to_account_id,
+                            new_data,
+                            &remote_data,
+                        )?;
+                        prepopulated_transaction.actions = vec![super::set_action(args, deposit)];
+                        return Ok(prepopulated_transaction);
+                    }
+                }
+                let mut local_components = crate::common::get_local_components(Some(deploy_to_account_id.clone()), Some(&network_config.network_name))?;
+                let mut components_to_prune = if prune {
+                    crate::common::get_remote_component_names(network_config, near_social_account_id, &deploy_to_account_id)?
+                        .into_iter()
+                        .filter(|component_name| !local_components.contains_key(component_name))
+                        .collect::<Vec<_>>()
+                } else {
+                    vec![]
+                };
+                if !only.is_empty() {
+                    local_components = crate::common::filter_components_by_patterns(local_components, &only)?;
+                }
+                if let Some(since) = &since {
+                    let changed_components = crate::common::get_components_changed_since(since)?;
+                    local_components.retain(|component_name, _| changed_components.contains(component_name));
+                    if local_components.is_empty() && components_to_prune.is_empty() {
+                        println!("There are no components changed since <{since}>. Goodbye.");
+                        return Ok(prepopulated_transaction);
+                    }
+                }
+                if local_components.is_empty() && components_to_prune.is_empty() {
+                    println!("There are no components in the project source folder. Goodbye.");
                     return Ok(prepopulated_transaction);
                 }
+                crate::common::ensure_valid_syntax(&local_components)?;
                 let local_component_name_list = local_components.keys().collect::<Vec<_>>();
-                let remote_components = crate::common::get_remote_components(network_config, local_component_name_list, near_social_account_id, &deploy_to_account_id)?;
+                let remote_components = if local_component_name_list.is_empty() {
+                    HashMap::new()
+                } else {
+                    crate::common::get_remote_components(network_config, local_component_name_list, near_social_account_id, &deploy_to_account_id, &near_primitives::types::Finality::Final.into())?
+                };
 
-                let components_to_deploy =
+                let mut components_to_deploy =
                     if !remote_components.is_empty() {
-                        let updated_components = crate::common::get_updated_components(local_components, &remote_components);
-                        if updated_components.is_empty() {
-                            println!("There are no new or modified components in the current ./src folder. Goodbye.");
+                        let updated_components = crate::common::get_updated_components(local_components, &remote_components, &diff_options);
+                        if updated_components.is_empty() && components_to_prune.is_empty() {
+                            println!("There are no new or modified components in the project source folder. Goodbye.");
                             return Ok(prepopulated_transaction);
                         }
                         updated_components
                     } else {
-                        println!("\nAll local components will be deployed to <{deploy_to_account_id}> as new.");
+                        if !local_components.is_empty() {
+                            println!("\nAll local components will be deployed to <{deploy_to_account_id}> as new.");
+                        }
                         local_components
                     };
+                if select_components && !components_to_deploy.is_empty() {
+                    let mut component_names = components_to_deploy.keys().cloned().collect::<Vec<_>>();
+                    component_names.sort();
+                    let selected_component_names = MultiSelect::new("Select the components to deploy:", component_names).prompt()?;
+                    if selected_component_names.is_empty() && components_to_prune.is_empty() {
+                        color_eyre::eyre::bail!("No components were selected for deployment");
+                    }
+                    components_to_deploy.retain(|component_name, _| selected_component_names.contains(component_name));
+                }
+                if !force && !components_to_deploy.is_empty() {
+                    super::ensure_no_remote_changes(
+                        network_config,
+                        near_social_account_id,
+                        &deploy_to_account_id,
+                        &components_to_deploy.keys().collect::<Vec<_>>(),
+                    )?;
+                }
 
-                let new_social_db_state = crate::socialdb_types::SocialDb {
-                    accounts: HashMap::from([(
-                        deploy_to_account_id.clone(),
-                        crate::socialdb_types::SocialDbAccountMetadata {
-                            components: components_to_deploy
-                        },
-                    )])
-                };
-                let new_social_db_state_json = serde_json::json!(&new_social_db_state);
-                let remote_social_db_state_json = serde_json::json!(&crate::socialdb_types::SocialDb {
-                    accounts: HashMap::from([(
-                        deploy_to_account_id.clone(),
-                        crate::socialdb_types::SocialDbAccountMetadata {
-                            components: remote_components
+                if !components_to_prune.is_empty() {
+                    println!("\nThe following components exist on <{deploy_to_account_id}>, but not in the project source folder, and will be removed:");
+                    for component_name in &components_to_prune {
+                        println!(" * {component_name}");
+                    }
+                    if !dry_run && !yes && !std::io::stdin().is_terminal() {
+                        color_eyre::eyre::bail!("Removing components requires a confirmation. Pass --yes to remove them when not running in a terminal.");
+                    }
+                    if !dry_run && !yes {
+                        #[derive(strum_macros::Display)]
+                        enum ConfirmOptions {
+                            #[strum(to_string = "Yes, I want to remove these components.")]
+                            Yes,
+                            #[strum(to_string = "No, I want to keep them.")]
+                            No,
                         }
-                    )])
-                });
+                        let select_choose_input = Select::new(
+                            "Do you want to remove these components?",
+                            vec![ConfirmOptions::Yes, ConfirmOptions::No],
+                        )
+                        .prompt()?;
+                        if let ConfirmOptions::No = select_choose_input {
+                            components_to_prune.clear();
+                            if components_to_deploy.is_empty() {
+                                println!("There are no components to deploy or remove. Goodbye.");
+                                return Ok(prepopulated_transaction);
+                            }
+                        }
+                    }
+                }
+                // The removed components go to the first batch, the next runs will not find them anymore
+                let mut prune_data = if components_to_prune.is_empty() {
+                    None
+                } else {
+                    let remote_data = crate::common::get_remote_components_data(network_config, near_social_account_id, &deploy_to_account_id, &components_to_prune, &near_primitives::types::Finality::Final.into())?;
+                    let mut null_data = remote_data.clone();
+                    crate::common::mark_leaf_values_as_null(&mut null_data);
+                    Some((null_data, remote_data))
+                };
+
+                let manifest = crate::project::Project::load()?.manifest;
+                let batch_limits = super::BatchLimits {
+                    max_bytes: manifest.deploy_batch_max_bytes.unwrap_or(crate::consts::DEFAULT_DEPLOY_BATCH_MAX_BYTES),
+                    max_components: manifest.deploy_batch_max_components.unwrap_or(crate::consts::DEFAULT_DEPLOY_BATCH_MAX_COMPONENTS),
+                };
+                let first_batch_reserved = match &prune_data {
+                    Some((null_data, _)) => (serde_json::to_string(null_data)?.len(), components_to_prune.len()),
+                    None => (0, 0),
+                };
+                let mut component_batches = super::split_into_batches(components_to_deploy, first_batch_reserved, batch_limits)?;
+                if component_batches.is_empty() {
+                    component_batches.push(HashMap::new());
+                }
 
-                let args = serde_json::to_string(&super::TransactionFunctionArgs {
-                    data: new_social_db_state,
-                })?
-                .into_bytes();
+                // The new data of the batch and the current remote data of the same keys
+                let get_batch_data = |batch: HashMap<crate::socialdb_types::ComponentName, crate::socialdb_types::SocialDbComponent>, prune_data: Option<(serde_json::Value, serde_json::Value)>| -> (serde_json::Value, serde_json::Value) {
+                    let remote_batch_components = remote_components
+                        .iter()
+                        .filter(|(component_name, _)| batch.contains_key(*component_name))
+                        .map(|(component_name, component)| (component_name.clone(), component.clone()))
+                        .collect();
+                    let mut new_social_db_state_json = serde_json::json!(&crate::socialdb_types::SocialDb {
+                        accounts: HashMap::from([(
+                            deploy_to_account_id.clone(),
+                            crate::socialdb_types::SocialDbAccountMetadata {
+                                components: batch
+                            },
+                        )])
+                    });
+                    let mut remote_social_db_state_json = serde_json::json!(&crate::socialdb_types::SocialDb {
+                        accounts: HashMap::from([(
+                            deploy_to_account_id.clone(),
+                            crate::socialdb_types::SocialDbAccountMetadata {
+                                components: remote_batch_components
+                            }
+                        )])
+                    });
+                    if let Some((null_data, remote_data)) = prune_data {
+                        crate::common::merge_json(&mut new_social_db_state_json, null_data);
+                        crate::common::merge_json(&mut remote_social_db_state_json, remote_data);
+                    }
+                    (new_social_db_state_json, remote_social_db_state_json)
+                };
+                let get_args_and_deposit = |(new_data, remote_data): (serde_json::Value, serde_json::Value)| {
+                    super::get_set_args_and_deposit(network_config, near_social_account_id, &deploy_to_account_id, new_data, &remote_data)
+                };
 
-                let json_rpc_client = network_config.json_rpc_client();
+                let batches_count = component_batches.len();
+                let mut batches_data = component_batches
+                    .into_iter()
+                    .map(|batch| get_batch_data(batch, prune_data.take()))
+                    .collect::<VecDeque<_>>();
 
-                let deposit = tokio::runtime::Runtime::new().unwrap().block_on(
-                    near_socialdb_client::required_deposit(
-                        &json_rpc_client,
-                        near_social_account_id,
-                        &deploy_to_account_id,
-                        &new_social_db_state_json,
-                        Some(&remote_social_db_state_json),
-                    )
+                if dry_run {
+                    for (batch_index, batch_data) in batches_data.into_iter().enumerate() {
+                        let (args, deposit) = get_args_and_deposit(batch_data)?;
+                        if batches_count > 1 {
+                            println!("\nDry run: batch {}/{batches_count}", batch_index + 1);
+                        }
+                        println!(
+                            "\nDry run: `set` function call arguments for <{near_social_account_id}>:\n{}",
+                            serde_json::to_string_pretty(&serde_json::from_slice::<serde_json::Value>(&args)?)?
+                        );
+                        println!("Arguments size: {} bytes", args.len());
+                        println!("Required deposit: {deposit}");
+                    }
+                    println!("\nThe transaction was not signed or sent.");
+                    return Ok(prepopulated_transaction);
+                }
+
+                if batches_count > 1 {
+                    println!("\nThe components will be deployed in <{batches_count}> transactions. Sending batch 1/{batches_count}.");
+                }
+                let (args, deposit) = get_args_and_deposit(
+                    batches_data
+                        .pop_front()
+                        .wrap_err("Internal error: there are no batches of components to deploy")?,
                 )?;
+                *batches.lock().unwrap() = super::DeployBatches {
+                    pending: batches_data,
+                    batches_count,
+                    current_batch: 1,
+                    last_nonce: None,
+                };
 
-                prepopulated_transaction.actions = vec![
-                    near_primitives::transaction::Action::FunctionCall(
-                        near_primitives::transaction::FunctionCallAction {
-                            method_name: "set".to_string(),
-                            args,
-                            gas: near_cli_rs::common::NearGas::from_tgas(300).as_gas(),
-                            deposit: deposit.to_yoctonear(),
-                        },
-                    )
-                ];
+                prepopulated_transaction.actions = vec![super::set_action(args, deposit)];
 
                 Ok(prepopulated_transaction)
             }
         });
 
+        let update_deposit_before_signing = super::update_deposit_before_signing(
+            item.signer_account_id.clone(),
+            item.deploy_to_account_id.clone(),
+        );
         let on_before_signing_callback: near_cli_rs::commands::OnBeforeSigningCallback =
             Arc::new({
-                let signer_account_id = item.signer_account_id.clone();
-                let deploy_to_account_id = item.deploy_to_account_id.clone();
+                let batches = item.batches.clone();
                 move |prepopulated_unsigned_transaction, network_config| {
-                    let json_rpc_client = network_config.json_rpc_client();
-                    if let near_primitives::transaction::Action::FunctionCall(action) =
-                        &mut prepopulated_unsigned_transaction.actions[0]
-                    {
-                        action.deposit = tokio::runtime::Runtime::new()
-                            .unwrap()
-                            .block_on(near_socialdb_client::get_deposit(
-                                &json_rpc_client,
-                                &signer_account_id,
-                                &prepopulated_unsigned_transaction.public_key,
-                                &deploy_to_account_id,
-                                "widget",
-                                &prepopulated_unsigned_transaction.receiver_id,
-                                near_cli_rs::common::NearBalance::from_yoctonear(action.deposit),
-                            ))?
-                            .to_yoctonear();
-                        Ok(())
-                    } else {
-                        color_eyre::eyre::bail!("Unexpected action to change components",);
+                    update_deposit_before_signing(
+                        prepopulated_unsigned_transaction,
+                        network_config,
+                    )?;
+                    let mut batches = batches.lock().unwrap();
+                    if let Some(last_nonce) = batches.last_nonce {
+                        prepopulated_unsigned_transaction.nonce =
+                            prepopulated_unsigned_transaction.nonce.max(last_nonce + 1);
                     }
+                    batches.last_nonce = Some(prepopulated_unsigned_transaction.nonce);
+                    Ok(())
                 }
             });
 
         let on_after_sending_transaction_callback: near_cli_rs::transaction_signature_options::OnAfterSendingTransactionCallback = Arc::new({
-            move |transaction_info, _network_config| {
+            move |transaction_info, network_config| {
                 let args = if let near_primitives::views::FinalExecutionStatus::SuccessValue(_) = transaction_info.status {
                     if let near_primitives::views::ActionView::FunctionCall { args, .. } =
                         &transaction_info.transaction.actions[0]
@@ -165,18 +334,50 @@ impl From<SignerContext> for near_cli_rs::commands::ActionContext {
                     color_eyre::eyre::bail!("Components deployment failed!");
                 };
 
-                let transaction_function_args: super::TransactionFunctionArgs =
+                let transaction_function_args: serde_json::Value =
                     serde_json::from_slice(args).wrap_err("Internal error: Could not parse SocialDB request that we just created.")?;
 
-                let social_account_metadata = transaction_function_args.data.accounts.get(item.deploy_to_account_id.as_ref())
+                let social_account_components = transaction_function_args["data"][item.deploy_to_account_id.as_str()]["widget"]
+                    .as_object()
                     .wrap_err("Internal error: Could not get metadata from SocialDB request that we just created.")?;
-                let updated_components = &social_account_metadata.components;
+                // Removed components have all their values set to `null`
+                let (removed_component_names, updated_component_names): (Vec<_>, Vec<_>) = social_account_components
+                    .iter()
+                    .partition(|(_, component)| component[""].is_null());
 
-                println!("\n<{}> components were successfully deployed:", updated_components.len());
-                for component in updated_components.keys() {
+                let batches = item.batches.lock().unwrap();
+                if batches.batches_count > 1 {
+                    println!(
+                        "\nBatch {}/{}: <{}> components were successfully deployed:",
+                        batches.current_batch,
+                        batches.batches_count,
+                        updated_component_names.len()
+                    );
+                } else {
+                    println!("\n<{}> components were successfully deployed:", updated_component_names.len());
+                }
+                drop(batches);
+                for (component, _) in &updated_component_names {
                     println!(" * {component}")
                 }
+                if !removed_component_names.is_empty() {
+                    println!("\n<{}> components were successfully removed:", removed_component_names.len());
+                    for (component, _) in &removed_component_names {
+                        println!(" * {component}")
+                    }
+                }
                 println!();
+
+                if item.skip_lockfile_update {
+                    return Ok(());
+                }
+                super::update_lockfile(
+                    network_config,
+                    transaction_info,
+                    &item.deploy_to_account_id,
+                    &updated_component_names.iter().map(|(component_name, _)| *component_name).collect::<Vec<_>>(),
+                    &removed_component_names.iter().map(|(component_name, _)| *component_name).collect::<Vec<_>>(),
+                )?;
                 Ok(())
             }
         });
@@ -194,6 +395,93 @@ impl From<SignerContext> for near_cli_rs::commands::ActionContext {
     }
 }
 
+impl interactive_clap::FromCli for Signer {
+    type FromCliContext = super::DeployCmdContext;
+    type FromCliError = color_eyre::eyre::Error;
+
+    fn from_cli(
+        optional_clap_variant: Option<<Self as interactive_clap::ToCli>::CliVariant>,
+        context: Self::FromCliContext,
+    ) -> interactive_clap::ResultFromCli<
+        <Self as interactive_clap::ToCli>::CliVariant,
+        Self::FromCliError,
+    >
+    where
+        Self: Sized + interactive_clap::ToCli,
+    {
+        let mut clap_variant = optional_clap_variant.unwrap_or_default();
+
+        if clap_variant.signer_account_id.is_none() {
+            clap_variant.signer_account_id = match Self::input_signer_account_id(&context) {
+                Ok(Some(signer_account_id)) => Some(signer_account_id),
+                Ok(None) => return interactive_clap::ResultFromCli::Cancel(Some(clap_variant)),
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+        }
+        let signer_account_id = clap_variant
+            .signer_account_id
+            .clone()
+            .expect("Unexpected error");
+
+        let new_context_scope = InteractiveClapContextScopeForSigner { signer_account_id };
+        let output_context = match SignerContext::from_previous_context(context, &new_context_scope)
+        {
+            Ok(new_context) => new_context,
+            Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+        };
+        let batches = output_context.batches.clone();
+
+        let mut optional_network_config = clap_variant.network_config.take().map(
+            |ClapNamedArgNetworkForTransactionArgsForSigner::NetworkConfig(cli_network_config)| {
+                cli_network_config
+            },
+        );
+        loop {
+            match near_cli_rs::network_for_transaction::NetworkForTransactionArgs::from_cli(
+                optional_network_config.take(),
+                output_context.clone().into(),
+            ) {
+                interactive_clap::ResultFromCli::Ok(cli_network_config) => {
+                    // The remaining batches are signed and sent with the options chosen for the first one
+                    if !batches.lock().unwrap().pending.is_empty() {
+                        optional_network_config = Some(cli_network_config);
+                        continue;
+                    }
+                    clap_variant.network_config = Some(
+                        ClapNamedArgNetworkForTransactionArgsForSigner::NetworkConfig(
+                            cli_network_config,
+                        ),
+                    );
+                    return interactive_clap::ResultFromCli::Ok(clap_variant);
+                }
+                interactive_clap::ResultFromCli::Cancel(optional_cli_network_config) => {
+                    clap_variant.network_config = optional_cli_network_config
+                        .map(ClapNamedArgNetworkForTransactionArgsForSigner::NetworkConfig);
+                    return interactive_clap::ResultFromCli::Cancel(Some(clap_variant));
+                }
+                interactive_clap::ResultFromCli::Back => {
+                    return interactive_clap::ResultFromCli::Back
+                }
+                interactive_clap::ResultFromCli::Err(optional_cli_network_config, err) => {
+                    clap_variant.network_config = optional_cli_network_config
+                        .map(ClapNamedArgNetworkForTransactionArgsForSigner::NetworkConfig);
+                    let batches = batches.lock().unwrap();
+                    let err = if batches.current_batch > 1 {
+                        err.wrap_err(format!(
+                            "<{}> of <{}> batches of components were deployed before the failure",
+                            batches.current_batch - 1,
+                            batches.batches_count
+                        ))
+                    } else {
+                        err
+                    };
+                    return interactive_clap::ResultFromCli::Err(Some(clap_variant), err);
+                }
+            }
+        }
+    }
+}
+
 impl Signer {
     fn input_signer_account_id(
         context: &super::DeployCmdContext,
@@ -201,7 +489,12 @@ impl Signer {
         loop {
             let signer_account_id: near_cli_rs::types::account_id::AccountId =
                 CustomType::new("What is the signer account ID?")
-                    .with_default(context.deploy_to_account_id.clone())
+                    .with_default(
+                        crate::project::Project::load()?
+                            .signer_account_id(context.deploy_to_account_id.as_ref())
+                            .map(Into::into)
+                            .unwrap_or_else(|| context.deploy_to_account_id.clone()),
+                    )
                     .prompt()?;
             if !near_cli_rs::common::is_account_exist(
                 &context.global_context.config.network_connection,
@@ -0,0 +1,119 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, interactive_clap::InteractiveClap)]
+#[interactive_clap(input_context = near_cli_rs::GlobalContext)]
+#[interactive_clap(output_context = DepsCmdContext)]
+pub struct DepsCmd {
+    #[interactive_clap(skip_default_input_arg)]
+    /// For which account do you want to check the dependencies of local components?
+    account_id: near_cli_rs::types::account_id::AccountId,
+    #[interactive_clap(named_arg)]
+    /// Select network
+    network_config: near_cli_rs::network::Network,
+}
+
+#[derive(Clone)]
+pub struct DepsCmdContext(near_cli_rs::network::NetworkContext);
+
+impl DepsCmdContext {
+    pub fn from_previous_context(
+        previous_context: near_cli_rs::GlobalContext,
+        scope: &<DepsCmd as interactive_clap::ToInteractiveClapContextScope>::InteractiveClapContextScope,
+    ) -> color_eyre::eyre::Result<Self> {
+        let account_id: near_primitives::types::AccountId = scope.account_id.clone().into();
+        let on_after_getting_network_callback: near_cli_rs::network::OnAfterGettingNetworkCallback =
+            std::sync::Arc::new({
+                let account_id = account_id.clone();
+                move |network_config| {
+                    let near_social_account_id =
+                        &crate::common::get_near_social_account_id(network_config)?;
+
+                    let local_components = crate::common::get_local_components(
+                        Some(account_id.clone()),
+                        Some(&network_config.network_name),
+                    )?;
+                    if local_components.is_empty() {
+                        println!("There are no components in the project source folder. Goodbye.");
+                        return Ok(());
+                    }
+
+                    let local_component_paths = local_components
+                        .keys()
+                        .map(|component_name| format!("{account_id}/widget/{component_name}"))
+                        .collect::<BTreeSet<_>>();
+                    let mut dependency_graph = BTreeMap::new();
+                    for (component_name, component) in local_components.iter() {
+                        dependency_graph.insert(
+                            component_name.clone(),
+                            crate::jsx::get_component_references(component_name, component.code())?,
+                        );
+                    }
+
+                    let external_references = dependency_graph
+                        .values()
+                        .flatten()
+                        .filter(|reference| !local_component_paths.contains(*reference))
+                        .collect::<BTreeSet<_>>();
+                    let remote_component_paths =
+                        crate::common::get_existing_remote_component_paths(
+                            network_config,
+                            external_references.into_iter().collect(),
+                            near_social_account_id,
+                        )?;
+
+                    let mut missing_references_count = 0;
+                    println!("\nDependencies of local components of <{account_id}>:");
+                    for (component_name, references) in dependency_graph.iter() {
+                        println!(" * {component_name}");
+                        for reference in references {
+                            let status = if local_component_paths.contains(reference) {
+                                "local"
+                            } else if remote_component_paths.contains(reference) {
+                                "remote"
+                            } else {
+                                missing_references_count += 1;
+                                "MISSING"
+                            };
+                            println!("    - {reference} ({status})");
+                        }
+                    }
+                    println!();
+
+                    if missing_references_count > 0 {
+                        color_eyre::eyre::bail!(
+                            "Found <{missing_references_count}> references to components that exist neither locally nor in SocialDB"
+                        );
+                    }
+                    println!("All the referenced components exist.");
+                    Ok(())
+                }
+            });
+        Ok(Self(near_cli_rs::network::NetworkContext {
+            config: previous_context.config,
+            interacting_with_account_ids: vec![account_id],
+            on_after_getting_network_callback,
+        }))
+    }
+}
+
+impl From<DepsCmdContext> for near_cli_rs::network::NetworkContext {
+    fn from(item: DepsCmdContext) -> Self {
+        item.0
+    }
+}
+
+impl DepsCmd {
+    pub fn input_account_id(
+        context: &near_cli_rs::GlobalContext,
+    ) -> color_eyre::eyre::Result<Option<near_cli_rs::types::account_id::AccountId>> {
+        crate::project::input_account_id(
+            "For which account do you want to check the dependencies of local components?",
+            || {
+                near_cli_rs::common::input_non_signer_account_id_from_used_account_list(
+                    &context.config.credentials_home_dir,
+                    "For which account do you want to check the dependencies of local components?",
+                )
+            },
+        )
+    }
+}
@@ -1,17 +1,44 @@
-use color_eyre::eyre::ContextCompat;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, interactive_clap::InteractiveClap)]
 #[interactive_clap(input_context = near_cli_rs::GlobalContext)]
 #[interactive_clap(output_context = DiffCmdContext)]
+#[interactive_clap(skip_default_from_cli)]
 pub struct DiffCmd {
     #[interactive_clap(skip_default_input_arg)]
     /// On which account do you want to compare local components?
     account_id: near_cli_rs::types::account_id::AccountId,
+    #[interactive_clap(long)]
+    #[interactive_clap(skip_default_input_arg)]
+    /// Output format: "inline" (default), "unified" (a patch for `git apply`) or "json"
+    format: Option<DiffFormat>,
+    #[interactive_clap(long)]
+    /// Do not consider whitespace-only changes (e.g. reindentation) as changes
+    ignore_whitespace: bool,
+    #[interactive_clap(long)]
+    /// Do not consider CRLF vs LF line endings as changes
+    ignore_line_endings: bool,
     #[interactive_clap(named_arg)]
     /// Select network
     network_config: near_cli_rs::network::Network,
 }
 
+#[derive(Debug, Default, Clone, Copy, strum_macros::Display, strum_macros::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum DiffFormat {
+    /// Colored changes for the terminal
+    #[default]
+    Inline,
+    /// Unified diff from the deployed to the local components
+    Unified,
+    /// Report with the status of every component
+    Json,
+}
+
+impl interactive_clap::ToCli for DiffFormat {
+    type CliVariant = DiffFormat;
+}
+
 #[derive(Clone)]
 pub struct DiffCmdContext(near_cli_rs::network::NetworkContext);
 
@@ -21,46 +48,62 @@ impl DiffCmdContext {
         scope: &<DiffCmd as interactive_clap::ToInteractiveClapContextScope>::InteractiveClapContextScope,
     ) -> color_eyre::eyre::Result<Self> {
         let account_id: near_primitives::types::AccountId = scope.account_id.clone().into();
+        let format = scope.format.unwrap_or_default();
+        let diff_options = crate::common::DiffOptions {
+            ignore_whitespace: scope.ignore_whitespace,
+            ignore_line_endings: scope.ignore_line_endings,
+        };
         let on_after_getting_network_callback: near_cli_rs::network::OnAfterGettingNetworkCallback =
             std::sync::Arc::new({
                 let account_id = account_id.clone();
                 move |network_config| {
-                    let near_social_account_id = crate::consts::NEAR_SOCIAL_ACCOUNT_ID
-                        .get(network_config.network_name.as_str())
-                        .wrap_err_with(|| {
-                            format!(
-                                "The <{}> network does not have a near-social contract.",
-                                network_config.network_name
-                            )
-                        })?;
-
-                    let local_components =
-                        crate::common::get_local_components(Some(account_id.clone()))?;
+                    let near_social_account_id =
+                        &crate::common::get_near_social_account_id(network_config)?;
+
+                    let local_components = crate::common::get_local_components(
+                        Some(account_id.clone()),
+                        Some(&network_config.network_name),
+                    )?;
                     if local_components.is_empty() {
-                        println!("There are no components in the current ./src folder. Goodbye.");
+                        eprintln!("There are no components in the project source folder. Goodbye.");
                         return Ok(());
                     }
-                    let local_component_name_list = local_components.keys().collect::<Vec<_>>();
+                    let remote_component_names = crate::common::get_remote_component_names(
+                        network_config,
+                        near_social_account_id,
+                        &account_id,
+                    )?;
+                    let mut component_name_list = local_components.keys().collect::<Vec<_>>();
+                    component_name_list.extend(
+                        remote_component_names.iter().filter(|component_name| {
+                            !local_components.contains_key(*component_name)
+                        }),
+                    );
 
                     let remote_components = crate::common::get_remote_components(
                         network_config,
-                        local_component_name_list,
+                        component_name_list,
                         near_social_account_id,
                         &account_id,
+                        &near_primitives::types::Finality::Final.into(),
                     )?;
+                    let component_diffs = crate::common::diff_components(
+                        &local_components,
+                        &remote_components,
+                        &diff_options,
+                    );
 
-                    if !remote_components.is_empty() {
-                        let updated_components = crate::common::get_updated_components(
-                            local_components,
-                            &remote_components,
-                        );
-                        if updated_components.is_empty() {
-                            println!("There are no new or modified components in the current ./src folder. Goodbye.");
-                            return Ok(());
-                        }
-                    } else {
-                        println!("\nAll local components are new to <{account_id}>.");
-                    };
+                    match format {
+                        DiffFormat::Inline => print_inline(&account_id, &component_diffs),
+                        DiffFormat::Unified => print!("{}", unified_diff(&component_diffs)?),
+                        DiffFormat::Json => println!(
+                            "{}",
+                            serde_json::to_string_pretty(&json_report(
+                                &account_id,
+                                &component_diffs
+                            ))?
+                        ),
+                    }
                     Ok(())
                 }
             });
@@ -78,13 +121,272 @@ impl From<DiffCmdContext> for near_cli_rs::network::NetworkContext {
     }
 }
 
+impl interactive_clap::FromCli for DiffCmd {
+    type FromCliContext = near_cli_rs::GlobalContext;
+    type FromCliError = color_eyre::eyre::Error;
+
+    fn from_cli(
+        optional_clap_variant: Option<<Self as interactive_clap::ToCli>::CliVariant>,
+        context: Self::FromCliContext,
+    ) -> interactive_clap::ResultFromCli<
+        <Self as interactive_clap::ToCli>::CliVariant,
+        Self::FromCliError,
+    >
+    where
+        Self: Sized + interactive_clap::ToCli,
+    {
+        let mut clap_variant = optional_clap_variant.unwrap_or_default();
+
+        if clap_variant.account_id.is_none() {
+            clap_variant.account_id = match Self::input_account_id(&context) {
+                Ok(Some(account_id)) => Some(account_id),
+                Ok(None) => return interactive_clap::ResultFromCli::Cancel(Some(clap_variant)),
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+        }
+        let account_id = clap_variant.account_id.clone().expect("Unexpected error");
+        let format = clap_variant.format;
+        let ignore_whitespace = clap_variant.ignore_whitespace;
+        let ignore_line_endings = clap_variant.ignore_line_endings;
+
+        let new_context_scope = InteractiveClapContextScopeForDiffCmd {
+            account_id,
+            format,
+            ignore_whitespace,
+            ignore_line_endings,
+        };
+        let output_context =
+            match DiffCmdContext::from_previous_context(context, &new_context_scope) {
+                Ok(new_context) => new_context,
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+
+        let optional_network_config = clap_variant.network_config.take().map(
+            |ClapNamedArgNetworkForDiffCmd::NetworkConfig(cli_network_config)| cli_network_config,
+        );
+        match near_cli_rs::network::Network::from_cli(
+            optional_network_config,
+            output_context.into(),
+        ) {
+            interactive_clap::ResultFromCli::Ok(cli_network_config) => {
+                clap_variant.network_config = Some(ClapNamedArgNetworkForDiffCmd::NetworkConfig(
+                    cli_network_config,
+                ));
+                interactive_clap::ResultFromCli::Ok(clap_variant)
+            }
+            interactive_clap::ResultFromCli::Cancel(optional_cli_network_config) => {
+                clap_variant.network_config =
+                    optional_cli_network_config.map(ClapNamedArgNetworkForDiffCmd::NetworkConfig);
+                interactive_clap::ResultFromCli::Cancel(Some(clap_variant))
+            }
+            interactive_clap::ResultFromCli::Back => interactive_clap::ResultFromCli::Back,
+            interactive_clap::ResultFromCli::Err(optional_cli_network_config, err) => {
+                clap_variant.network_config =
+                    optional_cli_network_config.map(ClapNamedArgNetworkForDiffCmd::NetworkConfig);
+                interactive_clap::ResultFromCli::Err(Some(clap_variant), err)
+            }
+        }
+    }
+}
+
 impl DiffCmd {
     pub fn input_account_id(
         context: &near_cli_rs::GlobalContext,
     ) -> color_eyre::eyre::Result<Option<near_cli_rs::types::account_id::AccountId>> {
-        near_cli_rs::common::input_non_signer_account_id_from_used_account_list(
-            &context.config.credentials_home_dir,
+        crate::project::input_account_id(
             "On which account do you want to compare local components?",
+            || {
+                near_cli_rs::common::input_non_signer_account_id_from_used_account_list(
+                    &context.config.credentials_home_dir,
+                    "On which account do you want to compare local components?",
+                )
+            },
         )
     }
 }
+
+fn print_inline(
+    account_id: &near_primitives::types::AccountId,
+    component_diffs: &BTreeMap<crate::socialdb_types::ComponentName, crate::common::ComponentDiff>,
+) {
+    if component_diffs
+        .values()
+        .all(|component_diff| component_diff.status() == crate::common::ComponentStatus::Added)
+    {
+        println!("\nAll local components are new to <{account_id}>.");
+        return;
+    }
+    for (component_name, component_diff) in component_diffs {
+        component_diff.print(component_name);
+    }
+    if !component_diffs.values().any(|component_diff| {
+        matches!(
+            component_diff.status(),
+            crate::common::ComponentStatus::Added | crate::common::ComponentStatus::Modified
+        )
+    }) {
+        println!("There are no new or modified components in the project source folder. Goodbye.");
+    }
+}
+
+/// Patch that turns the deployed components into the local ones, with the paths of the component
+/// source and metadata files relative to the project root. The new side is the code that would be
+/// deployed, so the patch only applies to the source files that are deployed as is (the ones
+/// with TypeScript, includes or placeholders are listed on stderr).
+fn unified_diff(
+    component_diffs: &BTreeMap<crate::socialdb_types::ComponentName, crate::common::ComponentDiff>,
+) -> color_eyre::eyre::Result<String> {
+    let project = crate::project::Project::load()?;
+    let lockfile = crate::lockfile::Lockfile::load(&project.bos_file_path())?;
+    let mut source_paths = BTreeMap::new();
+    for component_filepath in project.component_files()? {
+        source_paths.insert(
+            project.component_name(&component_filepath)?,
+            component_filepath,
+        );
+    }
+
+    let mut preprocessed_component_names = vec![];
+    let mut patch = String::new();
+    for (component_name, component_diff) in component_diffs {
+        let component_filepath = match source_paths.get(component_name) {
+            Some(component_filepath) => {
+                if let Some(new_component) = &component_diff.new_component {
+                    if std::fs::read_to_string(component_filepath)? != new_component.code() {
+                        preprocessed_component_names.push(component_name.as_str());
+                    }
+                }
+                component_filepath.clone()
+            }
+            // Remote-only components are removed from the path they were downloaded to
+            None => project
+                .src_dir()
+                .join(match lockfile.components.get(component_name) {
+                    Some(locked) => locked.path.clone(),
+                    None => std::path::PathBuf::from(component_name.replace('.', "/"))
+                        .with_extension("jsx"),
+                }),
+        };
+        let code_path = component_filepath.strip_prefix(&project.root)?;
+        let metadata_path = code_path.with_extension("metadata.json");
+        let old_path = |path: &std::path::Path, exists: bool| {
+            if exists {
+                format!("a/{}", path.display())
+            } else {
+                "/dev/null".to_string()
+            }
+        };
+        let new_path = |path: &std::path::Path, exists: bool| {
+            if exists {
+                format!("b/{}", path.display())
+            } else {
+                "/dev/null".to_string()
+            }
+        };
+
+        // The patch is made from the exact contents: the code of a downloaded component is saved
+        // as is, and the local component is deployed as is unless it is preprocessed
+        let code_of = |component: &Option<crate::socialdb_types::SocialDbComponent>| {
+            component
+                .as_ref()
+                .map_or(String::new(), |component| component.code().to_string())
+        };
+        if component_diff.code_diff.is_some() || component_diff.new_component.is_none() {
+            let old_code = code_of(&component_diff.old_component);
+            let new_code = code_of(&component_diff.new_component);
+            if old_code != new_code {
+                patch.push_str(&crate::common::unified_patch(
+                    &old_code,
+                    &new_code,
+                    &old_path(code_path, component_diff.old_component.is_some()),
+                    &new_path(code_path, component_diff.new_component.is_some()),
+                ));
+            }
+        }
+        let old_metadata = component_diff
+            .old_component
+            .as_ref()
+            .and_then(|component| component.metadata());
+        let new_metadata = component_diff
+            .new_component
+            .as_ref()
+            .and_then(|component| component.metadata());
+        if component_diff.has_metadata_changed()
+            || (component_diff.new_component.is_none() && old_metadata.is_some())
+        {
+            // Downloaded metadata is saved as pretty-printed JSON, the local file is used as is
+            let old_metadata_json = match old_metadata {
+                Some(metadata) => serde_json::to_string_pretty(metadata)?,
+                None => String::new(),
+            };
+            let new_metadata_json = match new_metadata {
+                Some(metadata) => std::fs::read_to_string(project.root.join(&metadata_path))
+                    .or_else(|_| serde_json::to_string_pretty(metadata))?,
+                None => String::new(),
+            };
+            if old_metadata_json != new_metadata_json {
+                patch.push_str(&crate::common::unified_patch(
+                    &old_metadata_json,
+                    &new_metadata_json,
+                    &old_path(&metadata_path, old_metadata.is_some()),
+                    &new_path(&metadata_path, new_metadata.is_some()),
+                ));
+            }
+        }
+    }
+    if !preprocessed_component_names.is_empty() {
+        eprintln!(
+            "The code of the following components is preprocessed before deploy, so the diff of their code does not apply to the source files: {}",
+            preprocessed_component_names.join(", ")
+        );
+    }
+    Ok(patch)
+}
+
+#[derive(serde::Serialize)]
+struct DiffReport<'a> {
+    account_id: &'a near_primitives::types::AccountId,
+    components: BTreeMap<&'a str, ComponentReport<'a>>,
+}
+
+#[derive(serde::Serialize)]
+struct ComponentReport<'a> {
+    status: crate::common::ComponentStatus,
+    /// Unified diff of the code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_diff: Option<String>,
+    /// Changed metadata fields
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    metadata_changes: &'a [crate::common::MetadataFieldChange],
+}
+
+fn json_report<'a>(
+    account_id: &'a near_primitives::types::AccountId,
+    component_diffs: &'a BTreeMap<
+        crate::socialdb_types::ComponentName,
+        crate::common::ComponentDiff,
+    >,
+) -> DiffReport<'a> {
+    let components = component_diffs
+        .iter()
+        .map(|(component_name, component_diff)| {
+            (
+                component_name.as_str(),
+                ComponentReport {
+                    status: component_diff.status(),
+                    code_diff: component_diff.code_diff.as_ref().map(|code_diff| {
+                        code_diff.unified(
+                            &format!("a/{component_name}"),
+                            &format!("b/{component_name}"),
+                        )
+                    }),
+                    metadata_changes: &component_diff.metadata_changes,
+                },
+            )
+        })
+        .collect();
+    DiffReport {
+        account_id,
+        components,
+    }
+}
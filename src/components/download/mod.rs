@@ -1,22 +1,34 @@
 use std::collections::HashMap;
 
 use color_eyre::eyre::{ContextCompat, WrapErr};
+use inquire::{CustomType, Select};
 use near_cli_rs::common::{CallResultExt, JsonRpcClientExt};
 
 #[derive(Debug, Clone, interactive_clap::InteractiveClap)]
 #[interactive_clap(input_context = near_cli_rs::GlobalContext)]
 #[interactive_clap(output_context = DownloadCmdContext)]
+#[interactive_clap(skip_default_from_cli)]
 pub struct DownloadCmd {
     #[interactive_clap(skip_default_input_arg)]
     /// Which account do you want to download components from?
     account_id: near_cli_rs::types::account_id::AccountId,
+    #[interactive_clap(long)]
+    #[interactive_clap(skip_default_input_arg)]
+    /// Download only the components matching the comma-separated names or glob patterns (e.g. "Dashboard.*")
+    only: Option<near_cli_rs::types::vec_string::VecString>,
+    #[interactive_clap(long)]
+    /// Also download the components from other accounts that the components refer to (into the vendor folder)
+    with_dependencies: bool,
+    #[interactive_clap(long)]
+    /// Overwrite the local files with the downloaded components instead of merging the local changes
+    force: bool,
     #[interactive_clap(named_arg)]
     /// Select network
-    network_config: near_cli_rs::network::Network,
+    network_config: near_cli_rs::network_view_at_block::NetworkViewAtBlockArgs,
 }
 
 #[derive(Clone)]
-pub struct DownloadCmdContext(near_cli_rs::network::NetworkContext);
+pub struct DownloadCmdContext(near_cli_rs::network_view_at_block::ArgsForViewContext);
 
 impl DownloadCmdContext {
     pub fn from_previous_context(
@@ -24,52 +36,66 @@ impl DownloadCmdContext {
         scope: &<DownloadCmd as interactive_clap::ToInteractiveClapContextScope>::InteractiveClapContextScope,
     ) -> color_eyre::eyre::Result<Self> {
         let account_id: near_primitives::types::AccountId = scope.account_id.clone().into();
+        let only: Vec<String> = scope.only.clone().map(Into::into).unwrap_or_default();
+        let with_dependencies = scope.with_dependencies;
+        let force = scope.force;
 
-        let on_after_getting_network_callback: near_cli_rs::network::OnAfterGettingNetworkCallback =
+        let on_after_getting_block_reference_callback: near_cli_rs::network_view_at_block::OnAfterGettingBlockReferenceCallback =
             std::sync::Arc::new({
-                move |network_config| {
-                    let near_social_account_id = match crate::consts::NEAR_SOCIAL_ACCOUNT_ID
-                        .get(&network_config.network_name.as_str())
-                    {
-                        Some(account_id) => account_id,
-                        None => {
-                            return Err(color_eyre::Report::msg(format!(
-                                "The <{}> network does not have a near-social contract.",
-                                network_config.network_name
-                            )))
-                        }
-                    };
+                move |network_config, block_reference| {
+                    let near_social_account_id =
+                        &crate::common::get_near_social_account_id(network_config)?;
 
-                    let input_args =
-                        serde_json::to_string(&crate::socialdb_types::SocialDbQueryWithOptions {
-                            keys: vec![format!("{account_id}/widget/*")],
-                            options: Some(crate::socialdb_types::SocialDbQueryOptions {
-                                return_type: "BlockHeight".to_string(),
-                            }),
+                    // All the queries are made at the same block, so the downloaded components are consistent
+                    let block_height = network_config
+                        .json_rpc_client()
+                        .blocking_call(near_jsonrpc_client::methods::block::RpcBlockRequest {
+                            block_reference: block_reference.clone(),
                         })
-                        .wrap_err("Internal error: could not serialize SocialDB input args")?;
+                        .wrap_err("Failed to fetch the block to download the components at")?
+                        .header
+                        .height;
+                    let block_reference = near_primitives::types::BlockReference::BlockId(
+                        near_primitives::types::BlockId::Height(block_height),
+                    );
 
-                    let call_result = network_config
-                        .json_rpc_client()
-                        .blocking_call_view_function(
-                            near_social_account_id,
-                            "keys",
-                            input_args.into_bytes(),
-                            near_primitives::types::Finality::Final.into(),
-                        )
-                        .wrap_err("Failed to fetch the components state from SocialDB")?;
-                    let keys: SocialDbKeysWithBlockHeights =
-                        call_result.parse_result_from_json()?;
+                    // SocialDB cannot match glob patterns, so they are applied to the list of all components
+                    let keys = if !only.is_empty()
+                        && !only.iter().any(|name| crate::common::is_component_name_pattern(name))
+                    {
+                        only.iter()
+                            .map(|component_name| format!("{account_id}/widget/{component_name}"))
+                            .collect()
+                    } else {
+                        vec![format!("{account_id}/widget/*")]
+                    };
+                    let mut keys = get_component_block_heights(
+                        network_config,
+                        near_social_account_id,
+                        keys,
+                        &block_reference,
+                    )?;
 
-                    let remote_social_account_components =
-                        if let Some(account_components) = keys.accounts.get(&account_id) {
+                    let mut remote_social_account_components =
+                        if let Some(account_components) = keys.accounts.remove(&account_id) {
                             account_components
-                        } else {
+                        } else if only.is_empty() {
                             println!(
                             "\nThere are currently no components in the account <{account_id}>.",
                         );
                             return Ok(());
+                        } else {
+                            SocialDbAccountComponentsWithBlockHeights {
+                                components: HashMap::new(),
+                            }
                         };
+                    if !only.is_empty() {
+                        remote_social_account_components.components =
+                            crate::common::filter_components_by_patterns(
+                                remote_social_account_components.components,
+                                &only,
+                            )?;
+                    }
                     let remote_component_name_list = remote_social_account_components
                         .components
                         .keys()
@@ -79,106 +105,648 @@ impl DownloadCmdContext {
                         remote_component_name_list,
                         near_social_account_id,
                         &account_id,
+                        &block_reference,
                     )?;
 
-                    let components_src_folder = std::path::PathBuf::from("./src");
-                    let mut components_paths: HashMap<&str, String> = HashMap::new();
+                    let project = crate::project::Project::load()?;
+                    let components_src_folder = project.src_dir();
+                    // The entries of the previously downloaded components are kept
+                    let lockfile_path = project.bos_file_path();
+                    let mut lockfile = crate::lockfile::Lockfile::load(&lockfile_path)?;
+                    let (base_components, unavailable_base_component_names) = if force {
+                        Default::default()
+                    } else {
+                        get_base_components(
+                            network_config,
+                            near_social_account_id,
+                            &account_id,
+                            &lockfile,
+                            &remote_social_account_components.components,
+                        )
+                    };
+                    let variables = project.variables(&network_config.network_name);
+                    let mut source_paths = HashMap::new();
+                    for component_filepath in project.component_files()? {
+                        source_paths.insert(project.component_name(&component_filepath)?, component_filepath);
+                    }
+                    let mut merged_files = vec![];
+                    let mut conflicted_files = vec![];
+                    let mut unmerged_components = vec![];
                     for (component_name, component) in remote_components.iter() {
-                        let mut component_path = components_src_folder.clone();
-                        component_path.extend(component_name.split('.'));
-                        std::fs::create_dir_all(component_path.parent().wrap_err_with(|| {
-                            format!(
-                                "Failed to get the parent path for {component_name} where the path is {}",
-                                component_path.display()
-                            )
-                        })?)?;
-                        let component_code_path = component_path.with_extension("jsx");
-                        std::fs::write(&component_code_path, component.code().as_bytes())
-                            .wrap_err_with(|| {
-                                format!(
-                                    "Failed to save component code into {}",
-                                    component_code_path.display()
-                                )
-                            })?;
-                        let block_height = remote_social_account_components
+                        let component_block_height = remote_social_account_components
                             .components
-                            .get(component_name);
-                        let near_path = format!(
-                            "{}/widget/{}@{}",
-                            account_id,
+                            .get(component_name)
+                            .copied();
+                        let locked = lockfile.components.get(component_name);
+                        // The downloaded code is saved into the existing source file of the component (e.g. `.tsx`)
+                        let code_path = locked
+                            .map(|locked| components_src_folder.join(&locked.path))
+                            .filter(|code_path| code_path.exists())
+                            .or_else(|| source_paths.get(component_name).cloned())
+                            .unwrap_or_else(|| default_code_path(&components_src_folder, component_name));
+                        let is_remote_changed = !matches!(
+                            locked,
+                            Some(locked) if locked.block_height(&account_id).is_some()
+                                && locked.block_height(&account_id) == component_block_height
+                        );
+                        let base = if is_remote_changed {
+                            base_components.get(component_name)
+                        } else {
+                            Some(component)
+                        };
+                        let remote_label = match component_block_height {
+                            Some(component_block_height) => {
+                                format!("{account_id}/widget/{component_name}@{component_block_height}")
+                            }
+                            None => format!("{account_id}/widget/{component_name}"),
+                        };
+                        let local_changes = if force {
+                            LocalChanges::Overwrite
+                        } else {
+                            let local_code = std::fs::read_to_string(&code_path).ok();
+                            let has_local_changes = local_code
+                                .as_deref()
+                                .is_some_and(|local_code| local_code != component.code());
+                            if is_remote_changed && has_local_changes {
+                                if unavailable_base_component_names.contains(component_name) {
+                                    unmerged_components.push(format!(
+                                        " * {component_name}: the base version recorded in .bos is not available on the RPC node (only archival nodes keep the state of old blocks)"
+                                    ));
+                                    continue;
+                                }
+                                if local_code.as_deref().is_some_and(|local_code| {
+                                    crate::preprocessing::needs_preprocessing(&code_path, local_code, &variables)
+                                }) {
+                                    unmerged_components.push(format!(
+                                        " * {component_name}: {} has TypeScript, includes or placeholders, so the downloaded code cannot be merged into it",
+                                        code_path.display()
+                                    ));
+                                    continue;
+                                }
+                            }
+                            LocalChanges::Merge {
+                                base,
+                                remote_label: &remote_label,
+                            }
+                        };
+                        let saved_component =
+                            save_component(&code_path, component_name, component, local_changes)?;
+                        merged_files.extend(saved_component.merged_files);
+                        conflicted_files.extend(saved_component.conflicted_files);
+                        // The components of other accounts are deployed as forks of the originals
+                        lockfile.record_downloaded(
                             component_name,
-                            block_height.unwrap()
+                            saved_component
+                                .code_path
+                                .strip_prefix(&components_src_folder)?
+                                .to_path_buf(),
+                            &account_id,
+                            component_block_height,
+                            !project.is_project_account(&account_id),
                         );
-                        components_paths.insert(component_name, near_path);
-                        if let Some(metadata) = component.metadata() {
-                            let metadata =
-                                serde_json::to_string_pretty(metadata).wrap_err_with(|| {
-                                    format!("Failed to serialize component metadata for {component_name}")
-                                })?;
-                            let component_metadata_path =
-                                component_path.with_extension("metadata.json");
-                            std::fs::write(&component_metadata_path, metadata.as_bytes())
-                                .wrap_err_with(|| {
-                                    format!(
-                                        "Failed to save component metadata into {}",
-                                        component_metadata_path.display()
-                                    )
-                                })?;
-                        }
                     }
-
-                    let meta_file_path = std::path::PathBuf::from("./src/.bos");
-                    let meta_file_content: String = components_paths
-                        .iter()
-                        .map(|(key, value)| format!("{}={}\n", key, value))
-                        .collect();
-                    std::fs::write(meta_file_path.clone(), meta_file_content.as_bytes())?;
+                    lockfile.save(&lockfile_path)?;
 
                     println!(
-                        "Components for account <{}> were downloaded into <{}> successfully",
+                        "Components for account <{}> were downloaded into <{}> successfully at block height <{}>",
                         account_id,
-                        components_src_folder.display()
+                        components_src_folder.display(),
+                        block_height
                     );
+                    if !merged_files.is_empty() {
+                        println!("\nLocal changes were merged into <{}> files:", merged_files.len());
+                        for filepath in merged_files {
+                            println!(" * {}", filepath.display());
+                        }
+                    }
+                    if !conflicted_files.is_empty() {
+                        println!(
+                            "\nLocal changes conflict with the downloaded ones in <{}> files (resolve the conflicts marked with `<<<<<<<` and `>>>>>>>`):",
+                            conflicted_files.len()
+                        );
+                        for filepath in conflicted_files {
+                            println!(" * {}", filepath.display());
+                        }
+                    }
 
+                    if with_dependencies {
+                        download_dependencies(
+                            network_config,
+                            near_social_account_id,
+                            &account_id,
+                            &remote_components,
+                            &project,
+                            block_height,
+                        )?;
+                    }
+
+                    if !unmerged_components.is_empty() {
+                        color_eyre::eyre::bail!(
+                            "The following components changed both locally and in SocialDB, but the local changes cannot be merged, so they were not downloaded:\n{}\nDownload them with --force to overwrite the local files.",
+                            unmerged_components.join("\n")
+                        );
+                    }
                     Ok(())
                 }
             });
-        Ok(Self(near_cli_rs::network::NetworkContext {
-            config: previous_context.config,
-            interacting_with_account_ids: vec![scope.account_id.clone().into()],
-            on_after_getting_network_callback,
-        }))
+        Ok(Self(
+            near_cli_rs::network_view_at_block::ArgsForViewContext {
+                config: previous_context.config,
+                interacting_with_account_ids: vec![scope.account_id.clone().into()],
+                on_after_getting_block_reference_callback,
+            },
+        ))
     }
 }
 
-impl From<DownloadCmdContext> for near_cli_rs::network::NetworkContext {
+impl From<DownloadCmdContext> for near_cli_rs::network_view_at_block::ArgsForViewContext {
     fn from(item: DownloadCmdContext) -> Self {
         item.0
     }
 }
 
+impl interactive_clap::FromCli for DownloadCmd {
+    type FromCliContext = near_cli_rs::GlobalContext;
+    type FromCliError = color_eyre::eyre::Error;
+
+    fn from_cli(
+        optional_clap_variant: Option<<Self as interactive_clap::ToCli>::CliVariant>,
+        context: Self::FromCliContext,
+    ) -> interactive_clap::ResultFromCli<
+        <Self as interactive_clap::ToCli>::CliVariant,
+        Self::FromCliError,
+    >
+    where
+        Self: Sized + interactive_clap::ToCli,
+    {
+        let mut clap_variant = optional_clap_variant.unwrap_or_default();
+
+        // The components are only asked for when the command is built interactively
+        let is_interactive = clap_variant.account_id.is_none();
+        if clap_variant.account_id.is_none() {
+            clap_variant.account_id = match Self::input_account_id(&context) {
+                Ok(Some(account_id)) => Some(account_id),
+                Ok(None) => return interactive_clap::ResultFromCli::Cancel(Some(clap_variant)),
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+        }
+        let account_id = clap_variant.account_id.clone().expect("Unexpected error");
+        if clap_variant.only.is_none() && is_interactive {
+            clap_variant.only = match Self::input_only(&context) {
+                Ok(optional_only) => optional_only,
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+        }
+        let only = clap_variant.only.clone();
+        let with_dependencies = clap_variant.with_dependencies;
+        let force = clap_variant.force;
+
+        let new_context_scope = InteractiveClapContextScopeForDownloadCmd {
+            account_id,
+            only,
+            with_dependencies,
+            force,
+        };
+        let output_context =
+            match DownloadCmdContext::from_previous_context(context, &new_context_scope) {
+                Ok(new_context) => new_context,
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+
+        let optional_network_config = clap_variant.network_config.take().map(
+            |ClapNamedArgNetworkViewAtBlockArgsForDownloadCmd::NetworkConfig(
+                cli_network_config,
+            )| { cli_network_config },
+        );
+        match near_cli_rs::network_view_at_block::NetworkViewAtBlockArgs::from_cli(
+            optional_network_config,
+            output_context.into(),
+        ) {
+            interactive_clap::ResultFromCli::Ok(cli_network_config) => {
+                clap_variant.network_config = Some(
+                    ClapNamedArgNetworkViewAtBlockArgsForDownloadCmd::NetworkConfig(
+                        cli_network_config,
+                    ),
+                );
+                interactive_clap::ResultFromCli::Ok(clap_variant)
+            }
+            interactive_clap::ResultFromCli::Cancel(optional_cli_network_config) => {
+                clap_variant.network_config = optional_cli_network_config
+                    .map(ClapNamedArgNetworkViewAtBlockArgsForDownloadCmd::NetworkConfig);
+                interactive_clap::ResultFromCli::Cancel(Some(clap_variant))
+            }
+            interactive_clap::ResultFromCli::Back => interactive_clap::ResultFromCli::Back,
+            interactive_clap::ResultFromCli::Err(optional_cli_network_config, err) => {
+                clap_variant.network_config = optional_cli_network_config
+                    .map(ClapNamedArgNetworkViewAtBlockArgsForDownloadCmd::NetworkConfig);
+                interactive_clap::ResultFromCli::Err(Some(clap_variant), err)
+            }
+        }
+    }
+}
+
 impl DownloadCmd {
+    fn input_only(
+        _context: &near_cli_rs::GlobalContext,
+    ) -> color_eyre::eyre::Result<Option<near_cli_rs::types::vec_string::VecString>> {
+        #[derive(strum_macros::Display)]
+        enum ConfirmOptions {
+            #[strum(to_string = "Yes, I want to download all the components.")]
+            Yes,
+            #[strum(to_string = "No, I want to select the components to download.")]
+            No,
+        }
+        let select_choose_input = Select::new(
+            "Do you want to download all the components of the account?",
+            vec![ConfirmOptions::Yes, ConfirmOptions::No],
+        )
+        .prompt()?;
+        if let ConfirmOptions::Yes = select_choose_input {
+            return Ok(None);
+        }
+        loop {
+            let input_components: near_cli_rs::types::vec_string::VecString = CustomType::new(
+                "Enter a comma-separated list of component names or glob patterns to download:",
+            )
+            .prompt()?;
+            if !input_components.0.is_empty() {
+                return Ok(Some(input_components));
+            }
+        }
+    }
+
     pub fn input_account_id(
         context: &near_cli_rs::GlobalContext,
     ) -> color_eyre::eyre::Result<Option<near_cli_rs::types::account_id::AccountId>> {
-        near_cli_rs::common::input_non_signer_account_id_from_used_account_list(
-            &context.config.credentials_home_dir,
+        crate::project::input_account_id(
             "Which account do you want to download components from?",
+            || {
+                near_cli_rs::common::input_non_signer_account_id_from_used_account_list(
+                    &context.config.credentials_home_dir,
+                    "Which account do you want to download components from?",
+                )
+            },
         )
     }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct SocialDbKeys {
-    #[serde(flatten)]
-    pub accounts:
-        std::collections::HashMap<near_primitives::types::AccountId, SocialDbAccountComponents>,
+pub fn get_component_block_heights(
+    network_config: &near_cli_rs::config::NetworkConfig,
+    near_social_account_id: &near_primitives::types::AccountId,
+    keys: Vec<String>,
+    block_reference: &near_primitives::types::BlockReference,
+) -> color_eyre::eyre::Result<SocialDbKeysWithBlockHeights> {
+    let input_args = serde_json::to_string(&crate::socialdb_types::SocialDbQueryWithOptions {
+        keys,
+        options: Some(crate::socialdb_types::SocialDbQueryOptions {
+            return_type: "BlockHeight".to_string(),
+        }),
+    })
+    .wrap_err("Internal error: could not serialize SocialDB input args")?;
+
+    network_config
+        .json_rpc_client()
+        .blocking_call_view_function(
+            near_social_account_id,
+            "keys",
+            input_args.into_bytes(),
+            block_reference.clone(),
+        )
+        .wrap_err("Failed to fetch the components state from SocialDB")?
+        .parse_result_from_json()
+        .wrap_err("SocialDB `keys` data response cannot be parsed")
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct SocialDbAccountComponents {
-    #[serde(rename = "widget")]
-    pub components: std::collections::HashMap<crate::socialdb_types::ComponentName, bool>,
+/// How the downloaded files are written over the existing local files
+enum LocalChanges<'a> {
+    /// The local files are overwritten
+    Overwrite,
+    /// The local changes made since the base version are merged into the downloaded version
+    /// (without a base version, the whole local files conflict with the downloaded ones)
+    Merge {
+        base: Option<&'a crate::socialdb_types::SocialDbComponent>,
+        remote_label: &'a str,
+    },
+}
+
+struct SavedComponent {
+    code_path: std::path::PathBuf,
+    merged_files: Vec<std::path::PathBuf>,
+    conflicted_files: Vec<std::path::PathBuf>,
+}
+
+/// Path of a new component source file: `<folder>/<path>.jsx`, where the path is the component
+/// name with `.` as the folder separator
+fn default_code_path(folder: &std::path::Path, component_name: &str) -> std::path::PathBuf {
+    let mut component_path = folder.to_path_buf();
+    component_path.extend(component_name.split('.'));
+    component_path.with_extension("jsx")
+}
+
+/// Saves the component code into `code_path` and the metadata next to it (`<name>.metadata.json`)
+fn save_component(
+    code_path: &std::path::Path,
+    component_name: &str,
+    component: &crate::socialdb_types::SocialDbComponent,
+    local_changes: LocalChanges,
+) -> color_eyre::eyre::Result<SavedComponent> {
+    std::fs::create_dir_all(code_path.parent().wrap_err_with(|| {
+        format!(
+            "Failed to get the parent path for {component_name} where the path is {}",
+            code_path.display()
+        )
+    })?)?;
+    let mut saved_component = SavedComponent {
+        code_path: code_path.to_path_buf(),
+        merged_files: vec![],
+        conflicted_files: vec![],
+    };
+
+    let base_code = match &local_changes {
+        LocalChanges::Overwrite => None,
+        LocalChanges::Merge { base, .. } => Some(base.map_or("", |base| base.code())),
+    };
+    save_file(
+        &saved_component.code_path.clone(),
+        component.code(),
+        base_code,
+        &local_changes,
+        &mut saved_component,
+    )
+    .wrap_err_with(|| {
+        format!(
+            "Failed to save component code into {}",
+            saved_component.code_path.display()
+        )
+    })?;
+
+    if let Some(metadata) = component.metadata() {
+        let to_json = |metadata| {
+            serde_json::to_string_pretty(metadata).wrap_err_with(|| {
+                format!("Failed to serialize component metadata for {component_name}")
+            })
+        };
+        let base_metadata = match &local_changes {
+            LocalChanges::Overwrite => None,
+            LocalChanges::Merge { base, .. } => Some(match base.and_then(|base| base.metadata()) {
+                Some(base_metadata) => to_json(base_metadata)?,
+                None => String::new(),
+            }),
+        };
+        let component_metadata_path = code_path.with_extension("metadata.json");
+        save_file(
+            &component_metadata_path,
+            &to_json(metadata)?,
+            base_metadata.as_deref(),
+            &local_changes,
+            &mut saved_component,
+        )
+        .wrap_err_with(|| {
+            format!(
+                "Failed to save component metadata into {}",
+                component_metadata_path.display()
+            )
+        })?;
+    }
+    Ok(saved_component)
+}
+
+/// Writes the downloaded content into the file. The local changes made since `base_content`
+/// are merged into the downloaded content.
+fn save_file(
+    filepath: &std::path::Path,
+    content: &str,
+    base_content: Option<&str>,
+    local_changes: &LocalChanges,
+    saved_component: &mut SavedComponent,
+) -> color_eyre::eyre::Result<()> {
+    if let (Some(base_content), LocalChanges::Merge { remote_label, .. }) =
+        (base_content, local_changes)
+    {
+        if let Ok(local_content) = std::fs::read_to_string(filepath) {
+            if local_content != base_content && local_content != content {
+                let merged =
+                    crate::merge::merge3(base_content, &local_content, content, remote_label);
+                std::fs::write(filepath, merged.text.as_bytes())?;
+                if merged.conflicts > 0 {
+                    saved_component
+                        .conflicted_files
+                        .push(filepath.to_path_buf());
+                } else {
+                    saved_component.merged_files.push(filepath.to_path_buf());
+                }
+                return Ok(());
+            }
+        }
+    }
+    std::fs::write(filepath, content.as_bytes())?;
+    Ok(())
+}
+
+/// Base versions (as recorded in the lockfile) of the components that changed in SocialDB since
+/// they were downloaded or deployed, and the names of the components whose base versions cannot
+/// be fetched (non-archival RPC nodes do not keep the state of old blocks)
+fn get_base_components(
+    network_config: &near_cli_rs::config::NetworkConfig,
+    near_social_account_id: &near_primitives::types::AccountId,
+    account_id: &near_primitives::types::AccountId,
+    lockfile: &crate::lockfile::Lockfile,
+    remote_block_heights: &HashMap<
+        crate::socialdb_types::ComponentName,
+        near_primitives::types::BlockHeight,
+    >,
+) -> (
+    HashMap<crate::socialdb_types::ComponentName, crate::socialdb_types::SocialDbComponent>,
+    std::collections::BTreeSet<crate::socialdb_types::ComponentName>,
+) {
+    let mut component_names_by_block_height: std::collections::BTreeMap<_, Vec<_>> =
+        std::collections::BTreeMap::new();
+    for (component_name, remote_block_height) in remote_block_heights {
+        let Some(locked) = lockfile.components.get(component_name) else {
+            continue;
+        };
+        match locked.block_height(account_id) {
+            Some(base_block_height) if base_block_height != *remote_block_height => {
+                component_names_by_block_height
+                    .entry(base_block_height)
+                    .or_default()
+                    .push(component_name);
+            }
+            _ => {}
+        }
+    }
+
+    let mut base_components = HashMap::new();
+    let mut unavailable_component_names = std::collections::BTreeSet::new();
+    for (block_height, component_names) in component_names_by_block_height {
+        match crate::common::get_remote_components(
+            network_config,
+            component_names.clone(),
+            near_social_account_id,
+            account_id,
+            &near_primitives::types::BlockReference::BlockId(
+                near_primitives::types::BlockId::Height(block_height),
+            ),
+        ) {
+            Ok(components) => base_components.extend(components),
+            Err(_) => unavailable_component_names.extend(component_names.into_iter().cloned()),
+        }
+    }
+    (base_components, unavailable_component_names)
+}
+
+/// Follows the `<Widget src>` and `VM.require` references of the downloaded components transitively
+/// and saves the referenced components of other accounts into `vendor/<account_id>/`
+fn download_dependencies(
+    network_config: &near_cli_rs::config::NetworkConfig,
+    near_social_account_id: &near_primitives::types::AccountId,
+    account_id: &near_primitives::types::AccountId,
+    downloaded_components: &HashMap<
+        crate::socialdb_types::ComponentName,
+        crate::socialdb_types::SocialDbComponent,
+    >,
+    project: &crate::project::Project,
+    block_height: near_primitives::types::BlockHeight,
+) -> color_eyre::eyre::Result<()> {
+    let block_reference = near_primitives::types::BlockReference::BlockId(
+        near_primitives::types::BlockId::Height(block_height),
+    );
+    let vendor_folder = project.root.join(crate::consts::VENDOR_FOLDER_NAME);
+
+    let mut visited_paths = downloaded_components
+        .keys()
+        .map(|component_name| format!("{account_id}/widget/{component_name}"))
+        .collect::<std::collections::BTreeSet<_>>();
+    let mut references = std::collections::BTreeSet::new();
+    for (component_name, component) in downloaded_components {
+        references.extend(get_component_references(component_name, component.code()));
+    }
+
+    // The entries of the dependencies downloaded before (e.g. with other `--only` patterns) are kept
+    let vendor_lockfile_path = vendor_folder.join(".bos");
+    let mut vendor_lockfile = crate::lockfile::Lockfile::load(&vendor_lockfile_path)?;
+    let mut downloaded_paths = vec![];
+    let mut missing_paths = vec![];
+    while !references.is_empty() {
+        // The next level of the dependency tree is fetched with one request per account
+        let mut component_names_by_account: HashMap<
+            near_primitives::types::AccountId,
+            Vec<crate::socialdb_types::ComponentName>,
+        > = HashMap::new();
+        for reference in std::mem::take(&mut references) {
+            if !visited_paths.insert(reference.clone()) {
+                continue;
+            }
+            let Some((dependency_account_id, component_name)) = reference
+                .split_once("/widget/")
+                .and_then(|(dependency_account_id, component_name)| {
+                    Some((dependency_account_id.parse().ok()?, component_name))
+                })
+            else {
+                println!("Skipping the reference <{reference}>: it is not a path to a component");
+                continue;
+            };
+            component_names_by_account
+                .entry(dependency_account_id)
+                .or_default()
+                .push(component_name.to_string());
+        }
+
+        for (dependency_account_id, component_names) in component_names_by_account {
+            let block_heights = get_component_block_heights(
+                network_config,
+                near_social_account_id,
+                component_names
+                    .iter()
+                    .map(|component_name| {
+                        format!("{dependency_account_id}/widget/{component_name}")
+                    })
+                    .collect(),
+                &block_reference,
+            )?
+            .accounts
+            .remove(&dependency_account_id)
+            .map(|account_components| account_components.components)
+            .unwrap_or_default();
+            let dependencies = crate::common::get_remote_components(
+                network_config,
+                component_names.iter().collect(),
+                near_social_account_id,
+                &dependency_account_id,
+                &block_reference,
+            )?;
+
+            for component_name in component_names {
+                let component_path = format!("{dependency_account_id}/widget/{component_name}");
+                let (Some(component), Some(component_block_height)) = (
+                    dependencies.get(&component_name),
+                    block_heights.get(&component_name),
+                ) else {
+                    missing_paths.push(component_path);
+                    continue;
+                };
+                // The vendored components are not edited locally
+                let component_code_path = save_component(
+                    &default_code_path(
+                        &vendor_folder.join(dependency_account_id.as_str()),
+                        &component_name,
+                    ),
+                    &component_name,
+                    component,
+                    LocalChanges::Overwrite,
+                )?
+                .code_path;
+                references.extend(get_component_references(&component_path, component.code()));
+                downloaded_paths.push(component_path.clone());
+                vendor_lockfile.components.insert(
+                    component_path,
+                    crate::lockfile::LockedComponent {
+                        path: component_code_path
+                            .strip_prefix(&vendor_folder)?
+                            .to_path_buf(),
+                        fork_of: None,
+                        block_heights: std::collections::BTreeMap::from([(
+                            dependency_account_id.clone(),
+                            *component_block_height,
+                        )]),
+                    },
+                );
+            }
+        }
+    }
+
+    if !downloaded_paths.is_empty() {
+        println!(
+            "\n<{}> dependencies were downloaded into <{}>:",
+            downloaded_paths.len(),
+            vendor_folder.display()
+        );
+        for component_path in downloaded_paths {
+            println!(" * {component_path}");
+        }
+        vendor_lockfile.save(&vendor_lockfile_path)?;
+    } else {
+        println!("\nThe downloaded components do not refer to components of other accounts.");
+    }
+    if !missing_paths.is_empty() {
+        println!("\nThe following referenced components do not exist in SocialDB:");
+        for component_path in missing_paths {
+            println!(" * {component_path}");
+        }
+    }
+    Ok(())
+}
+
+/// References of the component to other components. A component that fails to parse is skipped
+/// with a warning, so that it does not stop downloading the rest of the dependencies.
+fn get_component_references(
+    component_name: &str,
+    code: &str,
+) -> std::collections::BTreeSet<String> {
+    crate::jsx::get_component_references(component_name, code).unwrap_or_else(|err| {
+        eprintln!("{err:#}. Its dependencies are skipped.");
+        std::collections::BTreeSet::new()
+    })
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -193,5 +761,8 @@ pub struct SocialDbKeysWithBlockHeights {
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SocialDbAccountComponentsWithBlockHeights {
     #[serde(rename = "widget")]
-    pub components: std::collections::HashMap<crate::socialdb_types::ComponentName, i32>,
+    pub components: std::collections::HashMap<
+        crate::socialdb_types::ComponentName,
+        near_primitives::types::BlockHeight,
+    >,
 }
@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+use color_eyre::eyre::{ContextCompat, WrapErr};
+use inquire::{CustomType, Select};
+use near_cli_rs::common::{CallResultExt, JsonRpcClientExt};
+
+#[derive(Debug, Clone, interactive_clap::InteractiveClap)]
+#[interactive_clap(input_context = near_cli_rs::GlobalContext)]
+#[interactive_clap(output_context = HistoryCmdContext)]
+#[interactive_clap(skip_default_from_cli)]
+pub struct HistoryCmd {
+    #[interactive_clap(skip_default_input_arg)]
+    /// Which component do you want to see the history of (<account_id>/widget/<component_name>)?
+    component_path: String,
+    #[interactive_clap(long)]
+    #[interactive_clap(skip_default_input_arg)]
+    /// Block height of the version to show the diff from (the version before the one to show the diff to by default)
+    from_block: Option<near_primitives::types::BlockHeight>,
+    #[interactive_clap(long)]
+    #[interactive_clap(skip_default_input_arg)]
+    /// Block height of the version to show the diff to (the latest version by default)
+    to_block: Option<near_primitives::types::BlockHeight>,
+    #[interactive_clap(named_arg)]
+    /// Select network
+    network_config: near_cli_rs::network::Network,
+}
+
+#[derive(Clone)]
+pub struct HistoryCmdContext(near_cli_rs::network::NetworkContext);
+
+impl HistoryCmdContext {
+    pub fn from_previous_context(
+        previous_context: near_cli_rs::GlobalContext,
+        scope: &<HistoryCmd as interactive_clap::ToInteractiveClapContextScope>::InteractiveClapContextScope,
+    ) -> color_eyre::eyre::Result<Self> {
+        let (account_id, component_name) = parse_component_path(&scope.component_path)?;
+        let from_block = scope.from_block;
+        let to_block = scope.to_block;
+        // Without the block heights, the versions to compare are offered for selection in a terminal
+        let select_versions = scope.from_block.is_none()
+            && scope.to_block.is_none()
+            && std::io::stdin().is_terminal();
+
+        let on_after_getting_network_callback: near_cli_rs::network::OnAfterGettingNetworkCallback =
+            std::sync::Arc::new({
+                let account_id = account_id.clone();
+                move |network_config| {
+                    let near_social_account_id =
+                        &crate::common::get_near_social_account_id(network_config)?;
+                    let component_path = format!("{account_id}/widget/{component_name}");
+
+                    let input_args =
+                        serde_json::to_string(&crate::socialdb_types::SocialDbQueryWithOptions {
+                            keys: vec![component_path.clone()],
+                            options: Some(crate::socialdb_types::SocialDbQueryOptions {
+                                return_type: "History".to_string(),
+                            }),
+                        })
+                        .wrap_err("Internal error: could not serialize SocialDB input args")?;
+                    let mut block_heights = network_config
+                        .json_rpc_client()
+                        .blocking_call_view_function(
+                            near_social_account_id,
+                            "keys",
+                            input_args.into_bytes(),
+                            near_primitives::types::Finality::Final.into(),
+                        )
+                        .wrap_err("Failed to fetch the component history from SocialDB")?
+                        .parse_result_from_json::<SocialDbKeysWithHistory>()
+                        .wrap_err("SocialDB `keys` data response cannot be parsed")?
+                        .accounts
+                        .remove(&account_id)
+                        .and_then(|account_components| {
+                            account_components.components.get(&component_name).cloned()
+                        })
+                        .unwrap_or_default();
+                    if block_heights.is_empty() {
+                        println!("\nThere is no history of the component <{component_path}>.");
+                        return Ok(());
+                    }
+                    block_heights.sort();
+                    block_heights.dedup();
+
+                    let fetch_version = |block_height| -> color_eyre::eyre::Result<
+                        Option<crate::socialdb_types::SocialDbComponent>,
+                    > {
+                        Ok(crate::common::get_remote_components(
+                            network_config,
+                            vec![&component_name],
+                            near_social_account_id,
+                            &account_id,
+                            &near_primitives::types::BlockReference::BlockId(
+                                near_primitives::types::BlockId::Height(block_height),
+                            ),
+                        )
+                        .wrap_err_with(|| {
+                            format!(
+                                "Failed to fetch the version of <{component_path}> at block height <{block_height}>. The state of old blocks is only available on archival RPC nodes, so make sure that the RPC URL of <{}> points to one.",
+                                network_config.network_name
+                            )
+                        })?
+                        .remove(&component_name))
+                    };
+
+                    // The versions are fetched one by one, so the list is still printed when the
+                    // older states are not available (they are only kept by archival RPC nodes)
+                    println!("\nVersions of the component <{component_path}>:");
+                    let mut versions = HashMap::new();
+                    let mut unavailable_versions_count = 0;
+                    for block_height in &block_heights {
+                        match fetch_version(*block_height) {
+                            Ok(Some(component)) => {
+                                println!(
+                                    " * {component_path}@{block_height} ({} bytes)",
+                                    component.code().len()
+                                );
+                                versions.insert(*block_height, Some(component));
+                            }
+                            Ok(None) => {
+                                println!(" * {component_path}@{block_height} (deleted)");
+                                versions.insert(*block_height, None);
+                            }
+                            Err(_) => {
+                                println!(" * {component_path}@{block_height} (size unavailable)");
+                                unavailable_versions_count += 1;
+                            }
+                        }
+                    }
+                    if unavailable_versions_count > 0 {
+                        println!("\n<{unavailable_versions_count}> versions could not be fetched. The state of old blocks is only available on archival RPC nodes.");
+                    }
+                    println!();
+
+                    let (from_block, to_block) = if select_versions {
+                        if block_heights.len() < 2 || !select_diff(block_heights.len())? {
+                            return Ok(());
+                        }
+                        let from_block = Select::new(
+                            "Select the version to show the diff from:",
+                            block_heights.clone(),
+                        )
+                        .prompt()?;
+                        let to_block = Select::new(
+                            "Select the version to show the diff to:",
+                            block_heights.clone(),
+                        )
+                        .with_starting_cursor(block_heights.len() - 1)
+                        .prompt()?;
+                        (from_block, to_block)
+                    } else {
+                        let to_block = match to_block {
+                            Some(to_block) => to_block,
+                            None => *block_heights.last().wrap_err(
+                                "Internal error: there are no versions of the component",
+                            )?,
+                        };
+                        let from_block = match from_block {
+                            Some(from_block) => from_block,
+                            // The version right before the one to show the diff to
+                            None => match block_heights
+                                .iter()
+                                .rev()
+                                .find(|block_height| **block_height < to_block)
+                            {
+                                Some(from_block) => *from_block,
+                                None => {
+                                    println!("The version at block height <{to_block}> is the first version of the component.");
+                                    return Ok(());
+                                }
+                            },
+                        };
+                        (from_block, to_block)
+                    };
+
+                    let get_code = |block_height| -> color_eyre::eyre::Result<String> {
+                        if block_heights.binary_search(&block_height).is_err() {
+                            color_eyre::eyre::bail!(
+                                "There is no version of the component <{component_path}> at block height <{block_height}>"
+                            );
+                        }
+                        let component = match versions.get(&block_height) {
+                            Some(component) => component.clone(),
+                            None => fetch_version(block_height)?,
+                        };
+                        // Deleted components have no code
+                        Ok(component
+                            .map(|component| component.code().to_string())
+                            .unwrap_or_default())
+                    };
+                    let from_code = get_code(from_block)?;
+                    let to_code = get_code(to_block)?;
+
+                    println!("Diff of <{component_path}> between block heights <{from_block}> and <{to_block}>:");
+                    match crate::common::diff_code(
+                        &from_code,
+                        &to_code,
+                        &crate::common::DiffOptions::default(),
+                    ) {
+                        Some(code_diff) => code_diff.print(),
+                        None => println!("The code is the same."),
+                    }
+                    Ok(())
+                }
+            });
+        Ok(Self(near_cli_rs::network::NetworkContext {
+            config: previous_context.config,
+            interacting_with_account_ids: vec![account_id],
+            on_after_getting_network_callback,
+        }))
+    }
+}
+
+impl From<HistoryCmdContext> for near_cli_rs::network::NetworkContext {
+    fn from(item: HistoryCmdContext) -> Self {
+        item.0
+    }
+}
+
+impl interactive_clap::FromCli for HistoryCmd {
+    type FromCliContext = near_cli_rs::GlobalContext;
+    type FromCliError = color_eyre::eyre::Error;
+
+    fn from_cli(
+        optional_clap_variant: Option<<Self as interactive_clap::ToCli>::CliVariant>,
+        context: Self::FromCliContext,
+    ) -> interactive_clap::ResultFromCli<
+        <Self as interactive_clap::ToCli>::CliVariant,
+        Self::FromCliError,
+    >
+    where
+        Self: Sized + interactive_clap::ToCli,
+    {
+        let mut clap_variant = optional_clap_variant.unwrap_or_default();
+
+        if clap_variant.component_path.is_none() {
+            clap_variant.component_path = match Self::input_component_path(&context) {
+                Ok(Some(component_path)) => Some(component_path),
+                Ok(None) => return interactive_clap::ResultFromCli::Cancel(Some(clap_variant)),
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+        }
+        let component_path = clap_variant
+            .component_path
+            .clone()
+            .expect("Unexpected error");
+        let from_block = clap_variant.from_block;
+        let to_block = clap_variant.to_block;
+
+        let new_context_scope = InteractiveClapContextScopeForHistoryCmd {
+            component_path,
+            from_block,
+            to_block,
+        };
+        let output_context =
+            match HistoryCmdContext::from_previous_context(context, &new_context_scope) {
+                Ok(new_context) => new_context,
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+
+        let optional_network_config = clap_variant.network_config.take().map(
+            |ClapNamedArgNetworkForHistoryCmd::NetworkConfig(cli_network_config)| {
+                cli_network_config
+            },
+        );
+        match near_cli_rs::network::Network::from_cli(
+            optional_network_config,
+            output_context.into(),
+        ) {
+            interactive_clap::ResultFromCli::Ok(cli_network_config) => {
+                clap_variant.network_config = Some(
+                    ClapNamedArgNetworkForHistoryCmd::NetworkConfig(cli_network_config),
+                );
+                interactive_clap::ResultFromCli::Ok(clap_variant)
+            }
+            interactive_clap::ResultFromCli::Cancel(optional_cli_network_config) => {
+                clap_variant.network_config = optional_cli_network_config
+                    .map(ClapNamedArgNetworkForHistoryCmd::NetworkConfig);
+                interactive_clap::ResultFromCli::Cancel(Some(clap_variant))
+            }
+            interactive_clap::ResultFromCli::Back => interactive_clap::ResultFromCli::Back,
+            interactive_clap::ResultFromCli::Err(optional_cli_network_config, err) => {
+                clap_variant.network_config = optional_cli_network_config
+                    .map(ClapNamedArgNetworkForHistoryCmd::NetworkConfig);
+                interactive_clap::ResultFromCli::Err(Some(clap_variant), err)
+            }
+        }
+    }
+}
+
+impl HistoryCmd {
+    fn input_component_path(
+        _context: &near_cli_rs::GlobalContext,
+    ) -> color_eyre::eyre::Result<Option<String>> {
+        loop {
+            let component_path: String = CustomType::new(
+                "Which component do you want to see the history of (<account_id>/widget/<component_name>)?",
+            )
+            .prompt()?;
+            match parse_component_path(&component_path) {
+                Ok(_) => return Ok(Some(component_path)),
+                Err(err) => println!("{err}"),
+            }
+        }
+    }
+}
+
+fn select_diff(versions_count: usize) -> color_eyre::eyre::Result<bool> {
+    #[derive(strum_macros::Display)]
+    enum ConfirmOptions {
+        #[strum(to_string = "Yes, I want to see the diff between two versions.")]
+        Yes,
+        #[strum(to_string = "No, the list of versions is enough.")]
+        No,
+    }
+    println!("There are <{versions_count}> versions of the component.");
+    let select_choose_input = Select::new(
+        "Do you want to see the diff between two versions?",
+        vec![ConfirmOptions::Yes, ConfirmOptions::No],
+    )
+    .prompt()?;
+    Ok(matches!(select_choose_input, ConfirmOptions::Yes))
+}
+
+/// Splits `<account_id>/widget/<component_name>` into the account ID and the component name
+pub fn parse_component_path(
+    component_path: &str,
+) -> color_eyre::eyre::Result<(
+    near_primitives::types::AccountId,
+    crate::socialdb_types::ComponentName,
+)> {
+    let (account_id, component_name) = component_path
+        .split_once("/widget/")
+        .filter(|(_, component_name)| !component_name.is_empty())
+        .wrap_err_with(|| {
+            format!(
+                "<{component_path}> is not a component path (<account_id>/widget/<component_name>)"
+            )
+        })?;
+    let account_id = account_id
+        .parse()
+        .wrap_err_with(|| format!("<{account_id}> is not a valid account ID"))?;
+    Ok((account_id, component_name.to_string()))
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct SocialDbKeysWithHistory {
+    #[serde(flatten)]
+    pub accounts: HashMap<near_primitives::types::AccountId, SocialDbAccountComponentsWithHistory>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct SocialDbAccountComponentsWithHistory {
+    #[serde(rename = "widget")]
+    pub components:
+        HashMap<crate::socialdb_types::ComponentName, Vec<near_primitives::types::BlockHeight>>,
+}
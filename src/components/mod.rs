@@ -1,9 +1,16 @@
 use strum::{EnumDiscriminants, EnumIter, EnumMessage};
 
+mod check;
 mod delete;
 mod deploy;
+mod deps;
 mod diff;
 mod download;
+mod history;
+mod rollback;
+mod serve;
+mod status;
+mod watch;
 
 #[derive(Debug, Clone, interactive_clap::InteractiveClap)]
 #[interactive_clap(context = near_cli_rs::GlobalContext)]
@@ -25,11 +32,46 @@ pub enum ComponentsCommand {
     ))]
     /// Differences between component code for deployment
     Diff(self::diff::DiffCmd),
+    #[strum_discriminants(strum(
+        message = "status      -   Local and remote changes since the versions recorded in .bos"
+    ))]
+    /// Local and remote changes since the versions recorded in .bos
+    Status(self::status::StatusCmd),
+    #[strum_discriminants(strum(
+        message = "check       -   Check local components for syntax errors"
+    ))]
+    /// Check local components for syntax errors
+    Check(self::check::CheckCmd),
     #[strum_discriminants(strum(
         message = "deploy      -   Deploy components if code has changed"
     ))]
     /// Deploy сomponents if code has changed
     Deploy(self::deploy::DeployCmd),
+    #[strum_discriminants(strum(
+        message = "watch       -   Deploy changed components to a development account on every save"
+    ))]
+    /// Deploy changed components to a development account on every save
+    Watch(self::watch::WatchCmd),
+    #[strum_discriminants(strum(
+        message = "serve       -   Serve local components to a gateway for preview without deploying"
+    ))]
+    /// Serve local components to a gateway for preview without deploying
+    Serve(self::serve::ServeCmd),
+    #[strum_discriminants(strum(
+        message = "deps        -   Check references between components (<Widget src> and VM.require)"
+    ))]
+    /// Check references between components (<Widget src> and VM.require)
+    Deps(self::deps::DepsCmd),
+    #[strum_discriminants(strum(
+        message = "history     -   Versions of a deployed component and diffs between them"
+    ))]
+    /// Versions of a deployed component and diffs between them
+    History(self::history::HistoryCmd),
+    #[strum_discriminants(strum(
+        message = "rollback    -   Redeploy the version of a component at a given block height"
+    ))]
+    /// Redeploy the version of a component at a given block height
+    Rollback(self::rollback::RollbackCmd),
     #[strum_discriminants(strum(message = "delete      -   Delete components from account"))]
     /// Delete components from account
     Delete(self::delete::DeleteCmd),
@@ -0,0 +1,171 @@
+use inquire::CustomType;
+
+mod sign_as;
+
+#[derive(Debug, Clone, interactive_clap::InteractiveClap)]
+#[interactive_clap(input_context = near_cli_rs::GlobalContext)]
+#[interactive_clap(output_context = RollbackCmdContext)]
+#[interactive_clap(skip_default_from_cli)]
+pub struct RollbackCmd {
+    #[interactive_clap(skip_default_input_arg)]
+    /// Which account do you want to roll back the component on?
+    account_id: near_cli_rs::types::account_id::AccountId,
+    #[interactive_clap(skip_default_input_arg)]
+    /// Which component do you want to roll back?
+    component_name: String,
+    #[interactive_clap(long)]
+    #[interactive_clap(skip_default_input_arg)]
+    /// Block height of the version to roll back to
+    to_block: near_primitives::types::BlockHeight,
+    #[interactive_clap(named_arg)]
+    /// Specify signer account ID
+    sign_as: self::sign_as::Signer,
+}
+
+#[derive(Clone)]
+pub struct RollbackCmdContext {
+    pub global_context: near_cli_rs::GlobalContext,
+    pub account_id: near_cli_rs::types::account_id::AccountId,
+    pub component_name: crate::socialdb_types::ComponentName,
+    pub to_block: near_primitives::types::BlockHeight,
+}
+
+impl RollbackCmdContext {
+    pub fn from_previous_context(
+        previous_context: near_cli_rs::GlobalContext,
+        scope: &<RollbackCmd as interactive_clap::ToInteractiveClapContextScope>::InteractiveClapContextScope,
+    ) -> color_eyre::eyre::Result<Self> {
+        Ok(Self {
+            global_context: previous_context,
+            account_id: scope.account_id.clone(),
+            component_name: scope.component_name.clone(),
+            to_block: scope.to_block,
+        })
+    }
+}
+
+impl interactive_clap::FromCli for RollbackCmd {
+    type FromCliContext = near_cli_rs::GlobalContext;
+    type FromCliError = color_eyre::eyre::Error;
+
+    fn from_cli(
+        optional_clap_variant: Option<<Self as interactive_clap::ToCli>::CliVariant>,
+        context: Self::FromCliContext,
+    ) -> interactive_clap::ResultFromCli<
+        <Self as interactive_clap::ToCli>::CliVariant,
+        Self::FromCliError,
+    >
+    where
+        Self: Sized + interactive_clap::ToCli,
+    {
+        let mut clap_variant = optional_clap_variant.unwrap_or_default();
+
+        if clap_variant.account_id.is_none() {
+            clap_variant.account_id = match Self::input_account_id(&context) {
+                Ok(Some(account_id)) => Some(account_id),
+                Ok(None) => return interactive_clap::ResultFromCli::Cancel(Some(clap_variant)),
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+        }
+        let account_id = clap_variant.account_id.clone().expect("Unexpected error");
+        if clap_variant.component_name.is_none() {
+            clap_variant.component_name = match Self::input_component_name(&context) {
+                Ok(Some(component_name)) => Some(component_name),
+                Ok(None) => return interactive_clap::ResultFromCli::Cancel(Some(clap_variant)),
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+        }
+        let component_name = clap_variant
+            .component_name
+            .clone()
+            .expect("Unexpected error");
+        if clap_variant.to_block.is_none() {
+            clap_variant.to_block = match Self::input_to_block(&account_id, &component_name) {
+                Ok(Some(to_block)) => Some(to_block),
+                Ok(None) => return interactive_clap::ResultFromCli::Cancel(Some(clap_variant)),
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+        }
+        let to_block = clap_variant.to_block.expect("Unexpected error");
+
+        let new_context_scope = InteractiveClapContextScopeForRollbackCmd {
+            account_id,
+            component_name,
+            to_block,
+        };
+        let output_context =
+            match RollbackCmdContext::from_previous_context(context, &new_context_scope) {
+                Ok(new_context) => new_context,
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+
+        let optional_sign_as = clap_variant
+            .sign_as
+            .take()
+            .map(|ClapNamedArgSignerForRollbackCmd::SignAs(cli_sign_as)| cli_sign_as);
+        match <self::sign_as::Signer as interactive_clap::FromCli>::from_cli(
+            optional_sign_as,
+            output_context,
+        ) {
+            interactive_clap::ResultFromCli::Ok(cli_sign_as) => {
+                clap_variant.sign_as = Some(ClapNamedArgSignerForRollbackCmd::SignAs(cli_sign_as));
+                interactive_clap::ResultFromCli::Ok(clap_variant)
+            }
+            interactive_clap::ResultFromCli::Cancel(optional_cli_sign_as) => {
+                clap_variant.sign_as =
+                    optional_cli_sign_as.map(ClapNamedArgSignerForRollbackCmd::SignAs);
+                interactive_clap::ResultFromCli::Cancel(Some(clap_variant))
+            }
+            interactive_clap::ResultFromCli::Back => interactive_clap::ResultFromCli::Back,
+            interactive_clap::ResultFromCli::Err(optional_cli_sign_as, err) => {
+                clap_variant.sign_as =
+                    optional_cli_sign_as.map(ClapNamedArgSignerForRollbackCmd::SignAs);
+                interactive_clap::ResultFromCli::Err(Some(clap_variant), err)
+            }
+        }
+    }
+}
+
+impl RollbackCmd {
+    pub fn input_account_id(
+        context: &near_cli_rs::GlobalContext,
+    ) -> color_eyre::eyre::Result<Option<near_cli_rs::types::account_id::AccountId>> {
+        crate::project::input_account_id(
+            "Which account do you want to roll back the component on?",
+            || {
+                near_cli_rs::common::input_signer_account_id_from_used_account_list(
+                    &context.config.credentials_home_dir,
+                    "Which account do you want to roll back the component on?",
+                )
+            },
+        )
+    }
+
+    fn input_component_name(
+        _context: &near_cli_rs::GlobalContext,
+    ) -> color_eyre::eyre::Result<Option<String>> {
+        Ok(Some(
+            CustomType::new("Which component do you want to roll back?").prompt()?,
+        ))
+    }
+
+    /// Offers the base block height of the component recorded in `.bos` by download or deploy
+    fn input_to_block(
+        account_id: &near_cli_rs::types::account_id::AccountId,
+        component_name: &str,
+    ) -> color_eyre::eyre::Result<Option<near_primitives::types::BlockHeight>> {
+        let account_id: near_primitives::types::AccountId = account_id.clone().into();
+        let lockfile =
+            crate::lockfile::Lockfile::load(&crate::project::Project::load()?.bos_file_path())?;
+        let recorded_block_height = lockfile
+            .components
+            .get(component_name)
+            .and_then(|locked| locked.block_height(&account_id));
+        let mut input =
+            CustomType::new("Which block height do you want to roll the component back to?");
+        if let Some(recorded_block_height) = recorded_block_height {
+            input = input.with_default(recorded_block_height);
+        }
+        Ok(Some(input.prompt()?))
+    }
+}
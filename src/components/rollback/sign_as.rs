@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use inquire::{CustomType, Select};
+
+#[derive(Debug, Clone, interactive_clap::InteractiveClap)]
+#[interactive_clap(input_context = super::RollbackCmdContext)]
+#[interactive_clap(output_context = SignerContext)]
+pub struct Signer {
+    #[interactive_clap(skip_default_input_arg)]
+    /// What is the signer account ID?
+    signer_account_id: near_cli_rs::types::account_id::AccountId,
+    #[interactive_clap(named_arg)]
+    /// Select network
+    network_config: near_cli_rs::network_for_transaction::NetworkForTransactionArgs,
+}
+
+#[derive(Clone)]
+pub struct SignerContext {
+    global_context: near_cli_rs::GlobalContext,
+    account_id: near_primitives::types::AccountId,
+    component_name: crate::socialdb_types::ComponentName,
+    to_block: near_primitives::types::BlockHeight,
+    signer_account_id: near_primitives::types::AccountId,
+}
+
+impl SignerContext {
+    pub fn from_previous_context(
+        previous_context: super::RollbackCmdContext,
+        scope: &<Signer as interactive_clap::ToInteractiveClapContextScope>::InteractiveClapContextScope,
+    ) -> color_eyre::eyre::Result<Self> {
+        Ok(Self {
+            global_context: previous_context.global_context,
+            account_id: previous_context.account_id.into(),
+            component_name: previous_context.component_name,
+            to_block: previous_context.to_block,
+            signer_account_id: scope.signer_account_id.clone().into(),
+        })
+    }
+}
+
+impl From<SignerContext> for near_cli_rs::commands::ActionContext {
+    fn from(item: SignerContext) -> Self {
+        let account_id = item.account_id.clone();
+        let component_name = item.component_name.clone();
+        let to_block = item.to_block;
+        let signer_id = item.signer_account_id.clone();
+
+        let on_after_getting_network_callback: near_cli_rs::commands::OnAfterGettingNetworkCallback = Arc::new({
+            let account_id = account_id.clone();
+            let component_name = component_name.clone();
+            move |network_config| {
+                let near_social_account_id = &crate::common::get_near_social_account_id(network_config)?;
+                let mut prepopulated_transaction = near_cli_rs::commands::PrepopulatedTransaction {
+                    signer_id: signer_id.clone(),
+                    receiver_id: near_social_account_id.clone(),
+                    actions: vec![],
+                };
+                let component_path = format!("{account_id}/widget/{component_name}");
+
+                let previous_data = crate::common::get_remote_components_data(
+                    network_config,
+                    near_social_account_id,
+                    &account_id,
+                    std::slice::from_ref(&component_name),
+                    &near_primitives::types::BlockReference::BlockId(
+                        near_primitives::types::BlockId::Height(to_block),
+                    ),
+                )?;
+                if previous_data[account_id.as_str()]["widget"][&component_name][""].is_null() {
+                    color_eyre::eyre::bail!(
+                        "The component <{component_path}> did not exist at block height <{to_block}>"
+                    );
+                }
+                let current_data = crate::common::get_remote_components_data(
+                    network_config,
+                    near_social_account_id,
+                    &account_id,
+                    std::slice::from_ref(&component_name),
+                    &near_primitives::types::Finality::Final.into(),
+                )?;
+                if previous_data == current_data {
+                    println!("The component <{component_path}> has not changed since block height <{to_block}>. Goodbye.");
+                    return Ok(prepopulated_transaction);
+                }
+
+                let current_code = current_data[account_id.as_str()]["widget"][&component_name][""]
+                    .as_str()
+                    .unwrap_or_default();
+                let previous_code = previous_data[account_id.as_str()]["widget"][&component_name][""]
+                    .as_str()
+                    .unwrap_or_default();
+                println!("\nRolling back <{component_path}> to block height <{to_block}>:");
+                match crate::common::diff_code(current_code, previous_code, &crate::common::DiffOptions::default()) {
+                    Some(code_diff) => code_diff.print(),
+                    None => println!("The code is the same, only the metadata will be rolled back."),
+                }
+
+                // The keys that were added after the block are removed
+                let mut new_data = current_data.clone();
+                crate::common::mark_leaf_values_as_null(&mut new_data);
+                crate::common::merge_json(&mut new_data, previous_data);
+
+                let (args, deposit) = crate::components::deploy::get_set_args_and_deposit(
+                    network_config,
+                    near_social_account_id,
+                    &account_id,
+                    new_data,
+                    &current_data,
+                )?;
+                prepopulated_transaction.actions = vec![crate::components::deploy::set_action(args, deposit)];
+                Ok(prepopulated_transaction)
+            }
+        });
+
+        let on_after_sending_transaction_callback: near_cli_rs::transaction_signature_options::OnAfterSendingTransactionCallback = Arc::new({
+            let component_path = format!("{account_id}/widget/{component_name}");
+            move |transaction_info, _network_config| {
+                if let near_primitives::views::FinalExecutionStatus::SuccessValue(_) = transaction_info.status {
+                    println!("\nThe component <{component_path}> was successfully rolled back to block height <{to_block}>\n");
+                } else {
+                    color_eyre::eyre::bail!("The component <{component_path}> was not rolled back");
+                };
+                Ok(())
+            }
+        });
+
+        Self {
+            global_context: item.global_context,
+            interacting_with_account_ids: vec![item.signer_account_id.clone()],
+            on_after_getting_network_callback,
+            on_before_signing_callback: crate::components::deploy::update_deposit_before_signing(
+                item.signer_account_id,
+                item.account_id,
+            ),
+            on_before_sending_transaction_callback: Arc::new(
+                |_signed_transaction, _network_config, _message| Ok(()),
+            ),
+            on_after_sending_transaction_callback,
+        }
+    }
+}
+
+impl Signer {
+    fn input_signer_account_id(
+        context: &super::RollbackCmdContext,
+    ) -> color_eyre::eyre::Result<Option<near_cli_rs::types::account_id::AccountId>> {
+        loop {
+            let signer_account_id: near_cli_rs::types::account_id::AccountId =
+                CustomType::new("What is the signer account ID?")
+                    .with_default(
+                        crate::project::Project::load()?
+                            .signer_account_id(context.account_id.as_ref())
+                            .map(Into::into)
+                            .unwrap_or_else(|| context.account_id.clone()),
+                    )
+                    .prompt()?;
+            if !near_cli_rs::common::is_account_exist(
+                &context.global_context.config.network_connection,
+                signer_account_id.clone().into(),
+            ) {
+                println!("\nThe account <{signer_account_id}> does not yet exist.");
+                #[derive(strum_macros::Display)]
+                enum ConfirmOptions {
+                    #[strum(to_string = "Yes, I want to enter a new account name.")]
+                    Yes,
+                    #[strum(to_string = "No, I want to use this account name.")]
+                    No,
+                }
+                let select_choose_input = Select::new(
+                    "Do you want to enter another signer account id?",
+                    vec![ConfirmOptions::Yes, ConfirmOptions::No],
+                )
+                .prompt()?;
+                if let ConfirmOptions::No = select_choose_input {
+                    return Ok(Some(signer_account_id));
+                }
+            } else {
+                return Ok(Some(signer_account_id));
+            }
+        }
+    }
+}
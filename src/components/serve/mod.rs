@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use color_eyre::eyre::WrapErr;
+
+#[derive(Debug, Clone, interactive_clap::InteractiveClap)]
+#[interactive_clap(input_context = near_cli_rs::GlobalContext)]
+#[interactive_clap(output_context = ServeCmdContext)]
+#[interactive_clap(skip_default_from_cli)]
+pub struct ServeCmd {
+    #[interactive_clap(skip_default_input_arg)]
+    /// For which account do you want to serve the local components?
+    account_id: near_cli_rs::types::account_id::AccountId,
+    #[interactive_clap(long)]
+    #[interactive_clap(skip_default_input_arg)]
+    /// Port to listen on (3030 by default)
+    port: Option<Port>,
+    #[interactive_clap(named_arg)]
+    /// Select network
+    network_config: near_cli_rs::network::Network,
+}
+
+#[derive(Clone)]
+pub struct ServeCmdContext(near_cli_rs::network::NetworkContext);
+
+impl ServeCmdContext {
+    pub fn from_previous_context(
+        previous_context: near_cli_rs::GlobalContext,
+        scope: &<ServeCmd as interactive_clap::ToInteractiveClapContextScope>::InteractiveClapContextScope,
+    ) -> color_eyre::eyre::Result<Self> {
+        let account_id: near_primitives::types::AccountId = scope.account_id.clone().into();
+        let port = scope
+            .port
+            .map_or(crate::consts::DEFAULT_SERVE_PORT, |port| port.0);
+        let on_after_getting_network_callback: near_cli_rs::network::OnAfterGettingNetworkCallback =
+            std::sync::Arc::new({
+                let account_id = account_id.clone();
+                move |network_config| {
+                    let listener = TcpListener::bind(("127.0.0.1", port))
+                        .wrap_err_with(|| format!("Failed to listen on port {port}"))?;
+                    let preview = Preview {
+                        account_id: account_id.clone(),
+                        network_name: network_config.network_name.clone(),
+                        current: Mutex::new(CurrentRedirectMap::default()),
+                    };
+                    if let Err(err) = preview.redirect_map() {
+                        eprintln!("\nFailed to build the redirect map: {err:#}");
+                    }
+                    println!(
+                        "\nServing the local components of <{account_id}> at http://127.0.0.1:{port}. Press Ctrl+C to stop."
+                    );
+                    println!("Set this URL as the BOS Loader URL in the flags of the gateway to preview them.");
+
+                    // Every connection is handled in its own thread, so that a slow client does not
+                    // block the others
+                    let preview = Arc::new(preview);
+                    for stream in listener.incoming() {
+                        let mut stream = match stream {
+                            Ok(stream) => stream,
+                            Err(err) => {
+                                eprintln!("\nFailed to accept a connection: {err}");
+                                continue;
+                            }
+                        };
+                        let preview = preview.clone();
+                        std::thread::spawn(move || {
+                            if let Err(err) = handle_connection(&mut stream, &preview) {
+                                eprintln!("\nFailed to handle a request: {err:#}");
+                            }
+                        });
+                    }
+                    Ok(())
+                }
+            });
+        Ok(Self(near_cli_rs::network::NetworkContext {
+            config: previous_context.config,
+            interacting_with_account_ids: vec![account_id],
+            on_after_getting_network_callback,
+        }))
+    }
+}
+
+impl From<ServeCmdContext> for near_cli_rs::network::NetworkContext {
+    fn from(item: ServeCmdContext) -> Self {
+        item.0
+    }
+}
+
+impl interactive_clap::FromCli for ServeCmd {
+    type FromCliContext = near_cli_rs::GlobalContext;
+    type FromCliError = color_eyre::eyre::Error;
+
+    fn from_cli(
+        optional_clap_variant: Option<<Self as interactive_clap::ToCli>::CliVariant>,
+        context: Self::FromCliContext,
+    ) -> interactive_clap::ResultFromCli<
+        <Self as interactive_clap::ToCli>::CliVariant,
+        Self::FromCliError,
+    >
+    where
+        Self: Sized + interactive_clap::ToCli,
+    {
+        let mut clap_variant = optional_clap_variant.unwrap_or_default();
+
+        if clap_variant.account_id.is_none() {
+            clap_variant.account_id = match Self::input_account_id(&context) {
+                Ok(Some(account_id)) => Some(account_id),
+                Ok(None) => return interactive_clap::ResultFromCli::Cancel(Some(clap_variant)),
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+        }
+        let account_id = clap_variant.account_id.clone().expect("Unexpected error");
+        let port = clap_variant.port;
+
+        let new_context_scope = InteractiveClapContextScopeForServeCmd { account_id, port };
+        let output_context =
+            match ServeCmdContext::from_previous_context(context, &new_context_scope) {
+                Ok(new_context) => new_context,
+                Err(err) => return interactive_clap::ResultFromCli::Err(Some(clap_variant), err),
+            };
+
+        let optional_network_config = clap_variant.network_config.take().map(
+            |ClapNamedArgNetworkForServeCmd::NetworkConfig(cli_network_config)| cli_network_config,
+        );
+        match near_cli_rs::network::Network::from_cli(
+            optional_network_config,
+            output_context.into(),
+        ) {
+            interactive_clap::ResultFromCli::Ok(cli_network_config) => {
+                clap_variant.network_config = Some(ClapNamedArgNetworkForServeCmd::NetworkConfig(
+                    cli_network_config,
+                ));
+                interactive_clap::ResultFromCli::Ok(clap_variant)
+            }
+            interactive_clap::ResultFromCli::Cancel(optional_cli_network_config) => {
+                clap_variant.network_config =
+                    optional_cli_network_config.map(ClapNamedArgNetworkForServeCmd::NetworkConfig);
+                interactive_clap::ResultFromCli::Cancel(Some(clap_variant))
+            }
+            interactive_clap::ResultFromCli::Back => interactive_clap::ResultFromCli::Back,
+            interactive_clap::ResultFromCli::Err(optional_cli_network_config, err) => {
+                clap_variant.network_config =
+                    optional_cli_network_config.map(ClapNamedArgNetworkForServeCmd::NetworkConfig);
+                interactive_clap::ResultFromCli::Err(Some(clap_variant), err)
+            }
+        }
+    }
+}
+
+impl ServeCmd {
+    pub fn input_account_id(
+        context: &near_cli_rs::GlobalContext,
+    ) -> color_eyre::eyre::Result<Option<near_cli_rs::types::account_id::AccountId>> {
+        crate::project::input_account_id(
+            "For which account do you want to serve the local components?",
+            || {
+                near_cli_rs::common::input_non_signer_account_id_from_used_account_list(
+                    &context.config.credentials_home_dir,
+                    "For which account do you want to serve the local components?",
+                )
+            },
+        )
+    }
+}
+
+/// Port number (the `u16` range is checked when the arguments are parsed)
+#[derive(Debug, Clone, Copy)]
+pub struct Port(pub u16);
+
+impl std::fmt::Display for Port {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::str::FromStr for Port {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(port: &str) -> Result<Self, Self::Err> {
+        Ok(Self(port.parse()?))
+    }
+}
+
+impl interactive_clap::ToCli for Port {
+    type CliVariant = Port;
+}
+
+/// Redirect map of the local components, rebuilt when their source or metadata files change
+struct Preview {
+    account_id: near_primitives::types::AccountId,
+    network_name: String,
+    current: Mutex<CurrentRedirectMap>,
+}
+
+#[derive(Default)]
+struct CurrentRedirectMap {
+    snapshot: Option<super::watch::SourceSnapshot>,
+    redirect_map: Arc<String>,
+}
+
+impl Preview {
+    fn redirect_map(&self) -> color_eyre::eyre::Result<Arc<String>> {
+        let snapshot = super::watch::source_snapshot()?;
+        {
+            let current = self.current();
+            if current.snapshot.as_ref() == Some(&snapshot) {
+                return Ok(current.redirect_map.clone());
+            }
+        }
+        // The map is built without holding the lock, so the other requests are not blocked
+        let redirect_map = Arc::new(self.build_redirect_map()?);
+        let mut current = self.current();
+        current.snapshot = Some(snapshot);
+        current.redirect_map = redirect_map.clone();
+        Ok(redirect_map)
+    }
+
+    /// A thread that panicked while holding the lock could only leave a complete map behind
+    fn current(&self) -> std::sync::MutexGuard<'_, CurrentRedirectMap> {
+        self.current.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn build_redirect_map(&self) -> color_eyre::eyre::Result<String> {
+        let local_components = crate::common::get_local_components(
+            Some(self.account_id.clone()),
+            Some(&self.network_name),
+        )?;
+        // The format of the redirect map that gateways and bos-loader use
+        let components = local_components
+            .iter()
+            .map(|(component_name, component)| {
+                (
+                    format!("{}/widget/{component_name}", self.account_id),
+                    serde_json::json!({ "code": component.code() }),
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        let redirect_map = serde_json::to_string(&serde_json::json!({
+            "components": components,
+        }))?;
+        println!(
+            "\nBuilt the redirect map of <{}> components.",
+            components.len()
+        );
+        Ok(redirect_map)
+    }
+}
+
+/// Responds to a single HTTP request with the redirect map. Gateways fetch it from the browser,
+/// so the response allows cross-origin requests.
+fn handle_connection(stream: &mut TcpStream, preview: &Preview) -> color_eyre::eyre::Result<()> {
+    stream.set_read_timeout(Some(crate::consts::SERVE_CONNECTION_TIMEOUT))?;
+    stream.set_write_timeout(Some(crate::consts::SERVE_CONNECTION_TIMEOUT))?;
+    let mut reader = BufReader::new(&*stream);
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .wrap_err("Failed to read the request")?;
+    loop {
+        let mut header_line = String::new();
+        if reader
+            .read_line(&mut header_line)
+            .wrap_err("Failed to read the request")?
+            == 0
+            || header_line.trim().is_empty()
+        {
+            break;
+        }
+    }
+
+    let (status, content_type, body) = match request_line.split_whitespace().next() {
+        Some("GET") => match preview.redirect_map() {
+            Ok(redirect_map) => ("200 OK", "application/json", redirect_map.to_string()),
+            Err(err) => {
+                eprintln!("\nFailed to build the redirect map: {err:#}");
+                (
+                    "500 Internal Server Error",
+                    "text/plain",
+                    format!("{err:#}"),
+                )
+            }
+        },
+        Some("OPTIONS") => ("204 No Content", "text/plain", String::new()),
+        _ => ("405 Method Not Allowed", "text/plain", String::new()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: {content_type}\r\n\
+         Content-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: GET, OPTIONS\r\n\
+         Access-Control-Allow-Headers: *\r\n\
+         Access-Control-Allow-Private-Network: true\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+    .wrap_err("Failed to send the response")
+}
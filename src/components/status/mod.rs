@@ -0,0 +1,179 @@
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, interactive_clap::InteractiveClap)]
+#[interactive_clap(input_context = near_cli_rs::GlobalContext)]
+#[interactive_clap(output_context = StatusCmdContext)]
+pub struct StatusCmd {
+    #[interactive_clap(skip_default_input_arg)]
+    /// For which account do you want to check the status of local components?
+    account_id: near_cli_rs::types::account_id::AccountId,
+    #[interactive_clap(named_arg)]
+    /// Select network
+    network_config: near_cli_rs::network::Network,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum ComponentSyncStatus {
+    #[strum(to_string = "in sync")]
+    InSync,
+    #[strum(to_string = "locally modified")]
+    LocallyModified,
+    #[strum(to_string = "remotely changed")]
+    RemotelyChanged,
+    #[strum(to_string = "conflict (changed both locally and remotely)")]
+    Conflict,
+    /// There is no base version of the component for the account in `.bos`
+    #[strum(to_string = "untracked (no base version in .bos)")]
+    Untracked,
+}
+
+#[derive(Clone)]
+pub struct StatusCmdContext(near_cli_rs::network::NetworkContext);
+
+impl StatusCmdContext {
+    pub fn from_previous_context(
+        previous_context: near_cli_rs::GlobalContext,
+        scope: &<StatusCmd as interactive_clap::ToInteractiveClapContextScope>::InteractiveClapContextScope,
+    ) -> color_eyre::eyre::Result<Self> {
+        let account_id: near_primitives::types::AccountId = scope.account_id.clone().into();
+        let on_after_getting_network_callback: near_cli_rs::network::OnAfterGettingNetworkCallback =
+            std::sync::Arc::new({
+                let account_id = account_id.clone();
+                move |network_config| {
+                    let near_social_account_id =
+                        &crate::common::get_near_social_account_id(network_config)?;
+
+                    let local_components = crate::common::get_local_components(
+                        Some(account_id.clone()),
+                        Some(&network_config.network_name),
+                    )?;
+                    if local_components.is_empty() {
+                        println!("There are no components in the project source folder. Goodbye.");
+                        return Ok(());
+                    }
+                    let lockfile = crate::lockfile::Lockfile::load(
+                        &crate::project::Project::load()?.bos_file_path(),
+                    )?;
+                    let base_block_heights = local_components
+                        .keys()
+                        .filter_map(|component_name| {
+                            let locked = lockfile.components.get(component_name)?;
+                            Some((component_name.clone(), locked.block_height(&account_id)?))
+                        })
+                        .collect::<HashMap<_, _>>();
+
+                    let current_block_heights = if base_block_heights.is_empty() {
+                        HashMap::new()
+                    } else {
+                        super::download::get_component_block_heights(
+                            network_config,
+                            near_social_account_id,
+                            vec![format!("{account_id}/widget/*")],
+                            &near_primitives::types::Finality::Final.into(),
+                        )?
+                        .accounts
+                        .remove(&account_id)
+                        .map(|account_components| account_components.components)
+                        .unwrap_or_default()
+                    };
+                    let remotely_changed = base_block_heights
+                        .iter()
+                        .filter(|(component_name, base_block_height)| {
+                            current_block_heights.get(*component_name) != Some(base_block_height)
+                        })
+                        .map(|(component_name, _)| component_name.clone())
+                        .collect::<std::collections::HashSet<_>>();
+
+                    // The base versions of the components that did not change remotely are the current ones
+                    let mut base_components = crate::common::get_remote_components(
+                        network_config,
+                        base_block_heights
+                            .keys()
+                            .filter(|component_name| !remotely_changed.contains(*component_name))
+                            .collect(),
+                        near_social_account_id,
+                        &account_id,
+                        &near_primitives::types::Finality::Final.into(),
+                    )?;
+                    let mut remotely_changed_by_block_height: BTreeMap<_, Vec<_>> = BTreeMap::new();
+                    for component_name in &remotely_changed {
+                        remotely_changed_by_block_height
+                            .entry(base_block_heights[component_name])
+                            .or_default()
+                            .push(component_name);
+                    }
+                    for (block_height, component_names) in remotely_changed_by_block_height {
+                        base_components.extend(crate::common::get_remote_components(
+                            network_config,
+                            component_names,
+                            near_social_account_id,
+                            &account_id,
+                            &near_primitives::types::BlockReference::BlockId(
+                                near_primitives::types::BlockId::Height(block_height),
+                            ),
+                        )?);
+                    }
+
+                    let statuses = local_components
+                        .iter()
+                        .map(|(component_name, component)| {
+                            let status = if !base_block_heights.contains_key(component_name) {
+                                ComponentSyncStatus::Untracked
+                            } else {
+                                let is_locally_modified = crate::common::ComponentDiff::new(
+                                    base_components.get(component_name),
+                                    Some(component),
+                                    &crate::common::DiffOptions::default(),
+                                )
+                                .status()
+                                    != crate::common::ComponentStatus::Unchanged;
+                                match (
+                                    is_locally_modified,
+                                    remotely_changed.contains(component_name),
+                                ) {
+                                    (false, false) => ComponentSyncStatus::InSync,
+                                    (true, false) => ComponentSyncStatus::LocallyModified,
+                                    (false, true) => ComponentSyncStatus::RemotelyChanged,
+                                    (true, true) => ComponentSyncStatus::Conflict,
+                                }
+                            };
+                            (component_name, status)
+                        })
+                        .collect::<BTreeMap<_, _>>();
+
+                    println!("\nStatus of local components against <{account_id}>:");
+                    for (component_name, status) in statuses {
+                        println!(" * {component_name}: {status}");
+                    }
+                    Ok(())
+                }
+            });
+        Ok(Self(near_cli_rs::network::NetworkContext {
+            config: previous_context.config,
+            interacting_with_account_ids: vec![account_id],
+            on_after_getting_network_callback,
+        }))
+    }
+}
+
+impl From<StatusCmdContext> for near_cli_rs::network::NetworkContext {
+    fn from(item: StatusCmdContext) -> Self {
+        item.0
+    }
+}
+
+impl StatusCmd {
+    pub fn input_account_id(
+        context: &near_cli_rs::GlobalContext,
+    ) -> color_eyre::eyre::Result<Option<near_cli_rs::types::account_id::AccountId>> {
+        crate::project::input_account_id(
+            "For which account do you want to check the status of local components?",
+            || {
+                near_cli_rs::common::input_non_signer_account_id_from_used_account_list(
+                    &context.config.credentials_home_dir,
+                    "For which account do you want to check the status of local components?",
+                )
+            },
+        )
+    }
+}
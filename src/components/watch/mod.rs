@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+use clap::Parser;
+
+/// Modification times of the project manifest and the files every local component is built from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSnapshot {
+    manifest: Option<SystemTime>,
+    /// The source file, the metadata file and the included files of the component
+    components: BTreeMap<crate::socialdb_types::ComponentName, Vec<(PathBuf, Option<SystemTime>)>>,
+    /// Whether the code of other local components is inlined into the `VM.require` calls
+    inline_local_requires: bool,
+}
+
+#[derive(Debug, Clone, interactive_clap::InteractiveClap)]
+#[interactive_clap(input_context = near_cli_rs::GlobalContext)]
+#[interactive_clap(output_context = WatchCmdContext)]
+pub struct WatchCmd {
+    #[interactive_clap(long)]
+    #[interactive_clap(skip_default_input_arg)]
+    /// Which development account do you want to deploy the changed components to?
+    to: near_cli_rs::types::account_id::AccountId,
+    #[interactive_clap(named_arg)]
+    /// Select network
+    network_config: near_cli_rs::network::Network,
+}
+
+#[derive(Clone)]
+pub struct WatchCmdContext(near_cli_rs::network::NetworkContext);
+
+impl WatchCmdContext {
+    pub fn from_previous_context(
+        previous_context: near_cli_rs::GlobalContext,
+        scope: &<WatchCmd as interactive_clap::ToInteractiveClapContextScope>::InteractiveClapContextScope,
+    ) -> color_eyre::eyre::Result<Self> {
+        let account_id: near_primitives::types::AccountId = scope.to.clone().into();
+        let on_after_getting_network_callback: near_cli_rs::network::OnAfterGettingNetworkCallback =
+            std::sync::Arc::new({
+                let global_context = previous_context.clone();
+                let account_id = account_id.clone();
+                move |network_config| {
+                    let project = crate::project::Project::load()?;
+                    let signer_account_id = project
+                        .signer_account_id(&account_id)
+                        .unwrap_or_else(|| account_id.clone());
+                    println!(
+                        "\nWatching <{}> for changes to deploy to <{account_id}> on <{}>. Press Ctrl+C to stop.",
+                        project.src_dir().display(),
+                        network_config.network_name
+                    );
+
+                    let mut snapshot = source_snapshot()?;
+                    loop {
+                        let changed_component_names = wait_for_changes(&mut snapshot);
+                        println!(
+                            "\nChanged components: {}",
+                            changed_component_names
+                                .iter()
+                                .map(String::as_str)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        );
+                        if let Err(err) = deploy_components(
+                            &global_context,
+                            &network_config.network_name,
+                            &account_id,
+                            &signer_account_id,
+                            &changed_component_names,
+                        ) {
+                            eprintln!("\nFailed to deploy the changed components: {err:#}");
+                        }
+                        println!("\nWatching for changes...");
+                    }
+                }
+            });
+        Ok(Self(near_cli_rs::network::NetworkContext {
+            config: previous_context.config,
+            interacting_with_account_ids: vec![account_id],
+            on_after_getting_network_callback,
+        }))
+    }
+}
+
+impl From<WatchCmdContext> for near_cli_rs::network::NetworkContext {
+    fn from(item: WatchCmdContext) -> Self {
+        item.0
+    }
+}
+
+impl WatchCmd {
+    pub fn input_to(
+        context: &near_cli_rs::GlobalContext,
+    ) -> color_eyre::eyre::Result<Option<near_cli_rs::types::account_id::AccountId>> {
+        crate::project::input_account_id(
+            "Which development account do you want to deploy the changed components to?",
+            || {
+                near_cli_rs::common::input_signer_account_id_from_used_account_list(
+                    &context.config.credentials_home_dir,
+                    "Which development account do you want to deploy the changed components to?",
+                )
+            },
+        )
+    }
+}
+
+pub fn source_snapshot() -> color_eyre::eyre::Result<SourceSnapshot> {
+    let project = crate::project::Project::load()?;
+    let modified = |filepath: &Path| {
+        std::fs::metadata(filepath)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+    let mut components = BTreeMap::new();
+    for component_filepath in project.component_files()? {
+        let metadata_filepath = component_filepath.with_extension("metadata.json");
+        let included_filepaths = crate::preprocessing::included_files(&component_filepath);
+        let component_files = [component_filepath.clone(), metadata_filepath]
+            .into_iter()
+            .chain(included_filepaths)
+            .map(|filepath| {
+                let modification_time = modified(&filepath);
+                (filepath, modification_time)
+            })
+            .collect();
+        components.insert(
+            project.component_name(&component_filepath)?,
+            component_files,
+        );
+    }
+    Ok(SourceSnapshot {
+        manifest: modified(&project.root.join(crate::consts::PROJECT_MANIFEST_FILE_NAME)),
+        components,
+        inline_local_requires: project.manifest.inline_local_requires,
+    })
+}
+
+/// Polls the source files until some components change and then stay unchanged for the debounce
+/// delay. Removed components are not reported. A change of the manifest (e.g. of the config
+/// values) reports all the components, as does any change when the code of local components is
+/// inlined into the components that require them.
+fn wait_for_changes(
+    snapshot: &mut SourceSnapshot,
+) -> BTreeSet<crate::socialdb_types::ComponentName> {
+    let mut changed_component_names = BTreeSet::new();
+    let mut last_change_time: Option<Instant> = None;
+    loop {
+        std::thread::sleep(crate::consts::WATCH_POLL_INTERVAL);
+        let new_snapshot = match source_snapshot() {
+            Ok(new_snapshot) => new_snapshot,
+            // The project can be temporarily broken while it is being edited (e.g. bos.toml)
+            Err(err) => {
+                eprintln!("\nFailed to check the project source folder for changes: {err}");
+                continue;
+            }
+        };
+        let mut has_changes = false;
+        for (component_name, component_files) in &new_snapshot.components {
+            if snapshot.components.get(component_name) != Some(component_files) {
+                changed_component_names.insert(component_name.clone());
+                has_changes = true;
+            }
+        }
+        if new_snapshot.manifest != snapshot.manifest
+            || (has_changes && new_snapshot.inline_local_requires)
+        {
+            changed_component_names.extend(new_snapshot.components.keys().cloned());
+            has_changes = true;
+        }
+        *snapshot = new_snapshot;
+
+        if has_changes {
+            last_change_time = Some(Instant::now());
+        } else if last_change_time
+            .is_some_and(|time| time.elapsed() >= crate::consts::WATCH_DEBOUNCE_DELAY)
+        {
+            changed_component_names
+                .retain(|component_name| snapshot.components.contains_key(component_name));
+            if !changed_component_names.is_empty() {
+                return changed_component_names;
+            }
+            last_change_time = None;
+        }
+    }
+}
+
+/// Runs `components deploy` for the given components, signing with the key from the keychain.
+/// The lockfile (`.bos`) keeps the base versions of the main account.
+fn deploy_components(
+    global_context: &near_cli_rs::GlobalContext,
+    network_name: &str,
+    account_id: &near_primitives::types::AccountId,
+    signer_account_id: &near_primitives::types::AccountId,
+    component_names: &BTreeSet<crate::socialdb_types::ComponentName>,
+) -> color_eyre::eyre::Result<()> {
+    let only = component_names
+        .iter()
+        .map(|component_name| glob::Pattern::escape(component_name))
+        .collect::<Vec<_>>()
+        .join(",");
+    let cli_deploy_cmd = super::deploy::CliDeployCmd::try_parse_from([
+        "deploy",
+        account_id.as_str(),
+        "--only",
+        &only,
+        "--skip-lockfile-update",
+        "sign-as",
+        signer_account_id.as_str(),
+        "network-config",
+        network_name,
+        "sign-with-keychain",
+        "send",
+    ])?;
+
+    match <super::deploy::DeployCmd as interactive_clap::FromCli>::from_cli(
+        Some(cli_deploy_cmd),
+        global_context.clone(),
+    ) {
+        interactive_clap::ResultFromCli::Ok(_)
+        | interactive_clap::ResultFromCli::Cancel(_)
+        | interactive_clap::ResultFromCli::Back => Ok(()),
+        interactive_clap::ResultFromCli::Err(_, err) => Err(err),
+    }
+}
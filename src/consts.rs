@@ -2,6 +2,30 @@ use std::str::FromStr;
 
 use lazy_static::lazy_static;
 
+pub const PROJECT_MANIFEST_FILE_NAME: &str = "bos.toml";
+
+/// Folder in the project root for the components of other accounts downloaded as dependencies
+pub const VENDOR_FOLDER_NAME: &str = "vendor";
+
+pub const NEAR_SOCIAL_ACCOUNT_ID_ENV_VAR_PREFIX: &str = "BOS_SOCIAL_DB_CONTRACT_ACCOUNT_ID_";
+
+/// Prefix of the environment variables that `${env:<NAME>}` placeholders in components can use
+pub const COMPONENT_ENV_VAR_PREFIX: &str = "BOS_";
+
+/// Conservative limits for a single `set` transaction on deploy, so that it fits both
+/// the 300 Tgas budget and the transaction size limit
+pub const DEFAULT_DEPLOY_BATCH_MAX_BYTES: usize = 200 * 1024;
+pub const DEFAULT_DEPLOY_BATCH_MAX_COMPONENTS: usize = 100;
+
+/// How often `components watch` checks the source files for changes
+pub const WATCH_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(300);
+/// How long the source files have to stay unchanged before `components watch` deploys them,
+/// so that a burst of saves (e.g. formatting on save) results in a single deploy
+pub const WATCH_DEBOUNCE_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Port of the local preview server of `components serve` (the same as the one of bos-loader)
+pub const DEFAULT_SERVE_PORT: u16 = 3030;
+
 lazy_static! {
     pub static ref NEAR_SOCIAL_ACCOUNT_ID: std::collections::HashMap<&'static str, near_primitives::types::AccountId> =
         std::collections::HashMap::from([
@@ -15,3 +39,6 @@ lazy_static! {
             ),
         ]);
 }
+
+/// How long `components serve` waits for a client to send its request or receive the response
+pub const SERVE_CONNECTION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
@@ -0,0 +1,57 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{ContextCompat, WrapErr};
+
+/// Files (absolute paths) that differ between `git_ref` and the working tree, including untracked files
+pub fn changed_files_since(
+    dir: &Path,
+    git_ref: &str,
+) -> color_eyre::eyre::Result<HashSet<PathBuf>> {
+    let repository_root = PathBuf::from(run_git(dir, &["rev-parse", "--show-toplevel"])?.trim());
+    // Resolving the reference first keeps a `git_ref` that starts with `-` from being read as an option
+    let commit = run_git(
+        &repository_root,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            "--end-of-options",
+            &format!("{git_ref}^{{commit}}"),
+        ],
+    )
+    .ok()
+    .map(|commit| commit.trim().to_string())
+    .filter(|commit| !commit.is_empty())
+    .wrap_err_with(|| format!("`{git_ref}` is not a valid git commit"))?;
+    let changed_files = run_git(
+        &repository_root,
+        &["diff", "--name-only", "--no-renames", "-z", &commit, "--"],
+    )?;
+    let untracked_files = run_git(
+        &repository_root,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?;
+    Ok(changed_files
+        .split('\0')
+        .chain(untracked_files.split('\0'))
+        .filter(|path| !path.is_empty())
+        .map(|path| repository_root.join(path))
+        .collect())
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> color_eyre::eyre::Result<String> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .wrap_err("Failed to run git")?;
+    if !output.status.success() {
+        color_eyre::eyre::bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout).wrap_err("git output is not valid UTF-8")
+}
@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+
+use swc_core::common::{sync::Lrc, FileName, SourceMap, Spanned};
+use swc_core::ecma::ast::{
+    Callee, Expr, JSXAttrName, JSXAttrOrSpread, JSXAttrValue, JSXElementName, JSXExpr,
+    JSXOpeningElement, Lit, MemberProp, Script,
+};
+use swc_core::ecma::parser::{EsSyntax, Parser, StringInput, Syntax};
+use swc_core::ecma::visit::{Visit, VisitWith};
+
+/// Syntax error in the component source code (1-based line and column)
+#[derive(Debug, Clone)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl SyntaxError {
+    /// `wrapper_lines` is the number of lines added in front of the component source code to parse it
+    pub fn from_parser_error(
+        source_map: &SourceMap,
+        error: &swc_core::ecma::parser::error::Error,
+        wrapper_lines: usize,
+    ) -> Self {
+        let location = source_map.lookup_char_pos(error.span().lo);
+        Self {
+            line: location.line - wrapper_lines,
+            column: location.col_display + 1,
+            message: error.kind().msg().to_string(),
+        }
+    }
+}
+
+/// Parses the component source code (JSX with top-level `return` statements)
+pub fn parse(code: &str) -> Result<Script, SyntaxError> {
+    let source_map: Lrc<SourceMap> = Default::default();
+    let source_file = source_map.new_source_file(Lrc::new(FileName::Anon), code.to_string());
+    let mut parser = Parser::new(
+        Syntax::Es(EsSyntax {
+            jsx: true,
+            allow_return_outside_function: true,
+            ..Default::default()
+        }),
+        StringInput::from(&*source_file),
+        None,
+    );
+    parser
+        .parse_script()
+        .and_then(|script| match parser.take_errors().into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(script),
+        })
+        .map_err(|error| SyntaxError::from_parser_error(&source_map, &error, 0))
+}
+
+pub fn parse_component(component_name: &str, code: &str) -> color_eyre::eyre::Result<Script> {
+    parse(code).map_err(|error| {
+        color_eyre::eyre::eyre!(
+            "Failed to parse component <{component_name}> at {}:{}: {}",
+            error.line,
+            error.column,
+            error.message
+        )
+    })
+}
+
+/// Collects the static `<Widget src="..." />` and `VM.require("...")` references of the component
+/// (`@<block height>` suffixes are removed)
+pub fn get_component_references(
+    component_name: &str,
+    code: &str,
+) -> color_eyre::eyre::Result<BTreeSet<String>> {
+    let script = parse_component(component_name, code)?;
+    let mut collector = ReferenceCollector::default();
+    script.visit_with(&mut collector);
+    Ok(collector.references)
+}
+
+/// Static `VM.require("...")` calls of the component: byte range of the whole call expression
+/// in `code` and the required path as written in the source
+pub fn get_vm_require_calls(
+    component_name: &str,
+    code: &str,
+) -> color_eyre::eyre::Result<Vec<(std::ops::Range<usize>, String)>> {
+    let script = parse_component(component_name, code)?;
+    let mut collector = ReferenceCollector::default();
+    script.visit_with(&mut collector);
+    // The source file is the only one in its source map, so its positions start from 1
+    Ok(collector
+        .vm_require_calls
+        .into_iter()
+        .map(|(span, src)| ((span.lo.0 as usize - 1)..(span.hi.0 as usize - 1), src))
+        .collect())
+}
+
+#[derive(Default)]
+struct ReferenceCollector {
+    references: BTreeSet<String>,
+    vm_require_calls: Vec<(swc_core::common::Span, String)>,
+}
+
+impl ReferenceCollector {
+    fn insert(&mut self, reference: &str) {
+        let reference = reference
+            .split_once('@')
+            .map_or(reference, |(path, _block_height)| path);
+        self.references.insert(reference.to_string());
+    }
+}
+
+impl Visit for ReferenceCollector {
+    fn visit_jsx_opening_element(&mut self, element: &JSXOpeningElement) {
+        if matches!(&element.name, JSXElementName::Ident(name) if &*name.sym == "Widget") {
+            for attr in &element.attrs {
+                let JSXAttrOrSpread::JSXAttr(attr) = attr else {
+                    continue;
+                };
+                if !matches!(&attr.name, JSXAttrName::Ident(name) if &*name.sym == "src") {
+                    continue;
+                }
+                let src = match &attr.value {
+                    Some(JSXAttrValue::Str(src)) => src.value.as_atom().map(|src| src.to_string()),
+                    Some(JSXAttrValue::JSXExprContainer(container)) => match &container.expr {
+                        JSXExpr::Expr(expr) => static_string(expr),
+                        JSXExpr::JSXEmptyExpr(_) => None,
+                    },
+                    _ => None,
+                };
+                if let Some(src) = src {
+                    self.insert(&src);
+                }
+            }
+        }
+        element.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, call: &swc_core::ecma::ast::CallExpr) {
+        if let Callee::Expr(callee) = &call.callee {
+            if let Expr::Member(member) = &**callee {
+                let is_vm_require = matches!(&*member.obj, Expr::Ident(obj) if &*obj.sym == "VM")
+                    && matches!(&member.prop, MemberProp::Ident(prop) if &*prop.sym == "require");
+                if is_vm_require {
+                    if let Some(src) = call.args.first().and_then(|arg| static_string(&arg.expr)) {
+                        self.insert(&src);
+                        self.vm_require_calls.push((call.span, src));
+                    }
+                }
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
+
+/// String literal or template literal without expressions
+fn static_string(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(Lit::Str(value)) => value.value.as_atom().map(|value| value.to_string()),
+        Expr::Tpl(template) if template.exprs.is_empty() => {
+            template.quasis.first().map(|quasi| quasi.raw.to_string())
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::WrapErr;
+use near_primitives::types::{AccountId, BlockHeight};
+
+/// Lockfile (`.bos` in the source folder) with the base versions of the components: the versions
+/// they were downloaded or deployed at, on every account (e.g. on testnet and mainnet).
+///
+/// Example:
+/// ```toml
+/// [components."Dashboard.Main"]
+/// path = "Dashboard/Main.jsx"
+/// fork_of = "other.near/widget/Dashboard.Main@99999999"
+///
+/// [components."Dashboard.Main".block_heights]
+/// "example.near" = 100000000
+/// "example.testnet" = 150000000
+/// ```
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub components: BTreeMap<String, LockedComponent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LockedComponent {
+    /// Source file relative to the folder of the lockfile
+    pub path: PathBuf,
+    /// Component this one was forked from (`<account_id>/widget/<component_name>@<block height>`):
+    /// set when it is downloaded from an account that is not configured in `bos.toml`
+    pub fork_of: Option<String>,
+    /// Block heights of the base versions of the component in SocialDB by the account it was
+    /// downloaded from or deployed to
+    #[serde(default)]
+    pub block_heights: BTreeMap<AccountId, BlockHeight>,
+}
+
+impl LockedComponent {
+    /// Block height of the base version of the component on the account
+    pub fn block_height(&self, account_id: &AccountId) -> Option<BlockHeight> {
+        self.block_heights.get(account_id).copied()
+    }
+}
+
+impl Lockfile {
+    /// Reads the lockfile; a missing file is an empty lockfile
+    pub fn load(lockfile_path: &Path) -> color_eyre::eyre::Result<Self> {
+        let content = match std::fs::read_to_string(lockfile_path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("Failed to read {}", lockfile_path.display()))
+            }
+        };
+        match toml::from_str(&content) {
+            Ok(lockfile) => Ok(lockfile),
+            // `.bos` files of older versions have `<component_name>=<account_id>/widget/<component_name>@<block height>` lines
+            Err(err) => Self::parse_legacy(&content)
+                .map_err(|_| err)
+                .wrap_err_with(|| format!("Failed to parse {}", lockfile_path.display())),
+        }
+    }
+
+    fn parse_legacy(content: &str) -> color_eyre::eyre::Result<Self> {
+        let mut components = BTreeMap::new();
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let (component_name, source) = line
+                .split_once('=')
+                .ok_or_else(|| color_eyre::eyre::eyre!("Invalid line format: {line}"))?;
+            let (account_id, block_height) = source
+                .trim()
+                .split_once('@')
+                .and_then(|(path, block_height)| {
+                    Some((path.split_once("/widget/")?.0, block_height))
+                })
+                .ok_or_else(|| color_eyre::eyre::eyre!("Invalid component source: {source}"))?;
+            let component_name = component_name.trim();
+            let mut path = PathBuf::new();
+            path.extend(component_name.split('.'));
+            components.insert(
+                component_name.to_string(),
+                LockedComponent {
+                    path: path.with_extension("jsx"),
+                    fork_of: None,
+                    block_heights: BTreeMap::from([(
+                        account_id.parse()?,
+                        block_height.parse::<BlockHeight>()?,
+                    )]),
+                },
+            );
+        }
+        Ok(Self { components })
+    }
+
+    pub fn save(&self, lockfile_path: &Path) -> color_eyre::eyre::Result<()> {
+        let content = toml::to_string_pretty(self)
+            .wrap_err("Internal error: could not serialize the lockfile")?;
+        std::fs::write(
+            lockfile_path,
+            format!("# This file is generated by bos-cli.\n\n{content}"),
+        )
+        .wrap_err_with(|| format!("Failed to write {}", lockfile_path.display()))
+    }
+
+    /// Records the component downloaded from `account_id` at `block_height` (unknown block height
+    /// clears the base version on the account). `is_fork` marks the components of the accounts
+    /// that the project does not deploy to.
+    pub fn record_downloaded(
+        &mut self,
+        component_name: &str,
+        path: PathBuf,
+        account_id: &AccountId,
+        block_height: Option<BlockHeight>,
+        is_fork: bool,
+    ) {
+        let locked = self
+            .components
+            .entry(component_name.to_string())
+            .or_insert_with(|| LockedComponent {
+                path: path.clone(),
+                fork_of: None,
+                block_heights: BTreeMap::new(),
+            });
+        locked.path = path;
+        match block_height {
+            Some(block_height) => {
+                locked
+                    .block_heights
+                    .insert(account_id.clone(), block_height);
+            }
+            None => {
+                locked.block_heights.remove(account_id);
+            }
+        }
+        if is_fork {
+            locked.fork_of = Some(match block_height {
+                Some(block_height) => {
+                    format!("{account_id}/widget/{component_name}@{block_height}")
+                }
+                None => format!("{account_id}/widget/{component_name}"),
+            });
+        }
+    }
+
+    /// Records the components deployed to `account_id` at `block_height`; the base versions on
+    /// other accounts and the forks are kept
+    pub fn record_deployed(
+        &mut self,
+        account_id: &AccountId,
+        block_height: BlockHeight,
+        deployed_components: impl IntoIterator<Item = (String, PathBuf)>,
+    ) {
+        for (component_name, path) in deployed_components {
+            let locked = self
+                .components
+                .entry(component_name)
+                .or_insert_with(|| LockedComponent {
+                    path: path.clone(),
+                    fork_of: None,
+                    block_heights: BTreeMap::new(),
+                });
+            locked.path = path;
+            locked
+                .block_heights
+                .insert(account_id.clone(), block_height);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_legacy_lines() {
+        let lockfile =
+            Lockfile::parse_legacy("Dashboard.Main=example.near/widget/Dashboard.Main@100\n")
+                .unwrap();
+        assert_eq!(
+            lockfile.components["Dashboard.Main"],
+            LockedComponent {
+                path: PathBuf::from("Dashboard/Main.jsx"),
+                fork_of: None,
+                block_heights: BTreeMap::from([("example.near".parse().unwrap(), 100)]),
+            }
+        );
+        assert!(Lockfile::parse_legacy("@block_height=100\n").is_err());
+    }
+
+    #[test]
+    fn keeps_base_versions_and_fork_of_other_accounts() {
+        let mainnet: AccountId = "example.near".parse().unwrap();
+        let testnet: AccountId = "example.testnet".parse().unwrap();
+        let other: AccountId = "other.near".parse().unwrap();
+        let mut lockfile = Lockfile::default();
+        lockfile.record_downloaded("A", PathBuf::from("A.jsx"), &other, Some(10), true);
+        lockfile.record_deployed(&mainnet, 20, [("A".to_string(), PathBuf::from("A.jsx"))]);
+        lockfile.record_deployed(&testnet, 30, [("A".to_string(), PathBuf::from("A.jsx"))]);
+        lockfile.record_deployed(&mainnet, 40, [("A".to_string(), PathBuf::from("A.jsx"))]);
+
+        let locked = &lockfile.components["A"];
+        assert_eq!(locked.fork_of.as_deref(), Some("other.near/widget/A@10"));
+        assert_eq!(locked.block_height(&mainnet), Some(40));
+        assert_eq!(locked.block_height(&testnet), Some(30));
+        assert_eq!(locked.block_height(&other), Some(10));
+
+        // Downloading the project's own components keeps the fork
+        lockfile.record_downloaded("A", PathBuf::from("A.jsx"), &mainnet, Some(50), false);
+        let locked = &lockfile.components["A"];
+        assert_eq!(locked.fork_of.as_deref(), Some("other.near/widget/A@10"));
+        assert_eq!(locked.block_height(&mainnet), Some(50));
+    }
+}
@@ -10,8 +10,15 @@ use strum::{EnumDiscriminants, EnumIter, EnumMessage};
 pub mod common;
 mod components;
 pub mod consts;
+pub mod git;
+pub mod jsx;
+pub mod lockfile;
+pub mod merge;
+pub mod preprocessing;
+pub mod project;
 mod social_db;
 pub mod socialdb_types;
+pub mod typescript;
 
 /// near-cli is a toolbox for interacting with NEAR protocol
 
@@ -60,7 +67,7 @@ fn main() -> CliResult {
         ) {
             interactive_clap::ResultFromCli::Ok(cli_cmd)
             | interactive_clap::ResultFromCli::Cancel(Some(cli_cmd)) => {
-                println!(
+                eprintln!(
                     "Your console command:\n{} {}",
                     std::env::args().next().as_deref().unwrap_or("./bos"),
                     shell_words::join(cli_cmd.to_cli_args())
@@ -74,7 +81,7 @@ fn main() -> CliResult {
             interactive_clap::ResultFromCli::Back => {}
             interactive_clap::ResultFromCli::Err(optional_cli_cmd, err) => {
                 if let Some(cli_cmd) = optional_cli_cmd {
-                    println!(
+                    eprintln!(
                         "Your console command:\n{} {}",
                         std::env::args().next().as_deref().unwrap_or("./bos"),
                         shell_words::join(cli_cmd.to_cli_args())
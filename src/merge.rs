@@ -0,0 +1,224 @@
+use std::ops::Range;
+
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+pub struct MergeResult {
+    pub text: String,
+    /// Number of regions where the local and remote changes conflict (marked with
+    /// `<<<<<<<`, `=======` and `>>>>>>>` lines in the text)
+    pub conflicts: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Local,
+    Remote,
+}
+
+struct Hunk {
+    side: Side,
+    base_range: Range<usize>,
+    side_range: Range<usize>,
+}
+
+/// Line-based three-way merge of the local and remote changes made since the base version
+pub fn merge3(base: &str, local: &str, remote: &str, remote_label: &str) -> MergeResult {
+    // The last lines are compared regardless of the final newline, which is restored at the end
+    // from the side that changed it
+    let has_final_newline = |text: &str| text.is_empty() || text.ends_with('\n');
+    let final_newline = if has_final_newline(local) == has_final_newline(base) {
+        has_final_newline(remote)
+    } else {
+        has_final_newline(local)
+    };
+    let with_final_newline = |text: &str| {
+        if has_final_newline(text) {
+            text.to_string()
+        } else {
+            format!("{text}\n")
+        }
+    };
+    let (base, local, remote) = (
+        with_final_newline(base),
+        with_final_newline(local),
+        with_final_newline(remote),
+    );
+    let base_lines = base.split_inclusive('\n').collect::<Vec<_>>();
+    let local_lines = local.split_inclusive('\n').collect::<Vec<_>>();
+    let remote_lines = remote.split_inclusive('\n').collect::<Vec<_>>();
+
+    let mut hunks = changed_hunks(Side::Local, &base_lines, &local_lines);
+    hunks.extend(changed_hunks(Side::Remote, &base_lines, &remote_lines));
+    hunks.sort_by_key(|hunk| (hunk.base_range.start, hunk.base_range.end));
+
+    let mut text = String::with_capacity(local.len().max(remote.len()));
+    let mut conflicts = 0;
+    let mut base_position = 0;
+    let mut hunks = hunks.into_iter().peekable();
+    while let Some(first_hunk) = hunks.next() {
+        // Changes that overlap or touch each other are merged as one region
+        let region_start = first_hunk.base_range.start;
+        let mut region_end = first_hunk.base_range.end;
+        let mut region_hunks = vec![first_hunk];
+        while let Some(hunk) = hunks.next_if(|hunk| hunk.base_range.start <= region_end) {
+            region_end = region_end.max(hunk.base_range.end);
+            region_hunks.push(hunk);
+        }
+
+        text.extend(base_lines[base_position..region_start].iter().copied());
+        base_position = region_end;
+
+        let has_local_changes = region_hunks.iter().any(|hunk| hunk.side == Side::Local);
+        let has_remote_changes = region_hunks.iter().any(|hunk| hunk.side == Side::Remote);
+        let region = |side, side_lines: &[&str]| {
+            apply_hunks(
+                &base_lines,
+                region_start..region_end,
+                &region_hunks,
+                side,
+                side_lines,
+            )
+        };
+        let local_region = region(Side::Local, &local_lines);
+        let remote_region = region(Side::Remote, &remote_lines);
+        if !has_remote_changes || local_region == remote_region {
+            text.push_str(&local_region);
+        } else if !has_local_changes {
+            text.push_str(&remote_region);
+        } else {
+            conflicts += 1;
+            text.push_str("<<<<<<< local\n");
+            push_with_newline(&mut text, &local_region);
+            text.push_str("=======\n");
+            push_with_newline(&mut text, &remote_region);
+            text.push_str(&format!(">>>>>>> {remote_label}\n"));
+        }
+    }
+    text.extend(base_lines[base_position..].iter().copied());
+    if !final_newline && text.ends_with('\n') {
+        text.pop();
+    }
+
+    MergeResult { text, conflicts }
+}
+
+fn changed_hunks(side: Side, base_lines: &[&str], side_lines: &[&str]) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = vec![];
+    for (tag, base_range, side_range) in
+        capture_diff_slices(Algorithm::Myers, base_lines, side_lines)
+            .iter()
+            .map(|op| op.as_tag_tuple())
+    {
+        if tag == DiffTag::Equal {
+            continue;
+        }
+        match hunks.last_mut() {
+            Some(last)
+                if last.base_range.end == base_range.start
+                    && last.side_range.end == side_range.start =>
+            {
+                last.base_range.end = base_range.end;
+                last.side_range.end = side_range.end;
+            }
+            _ => hunks.push(Hunk {
+                side,
+                base_range,
+                side_range,
+            }),
+        }
+    }
+    hunks
+}
+
+/// The base lines of the region with the changes of one side applied
+fn apply_hunks(
+    base_lines: &[&str],
+    region: Range<usize>,
+    hunks: &[Hunk],
+    side: Side,
+    side_lines: &[&str],
+) -> String {
+    let mut text = String::new();
+    let mut base_position = region.start;
+    for hunk in hunks.iter().filter(|hunk| hunk.side == side) {
+        text.extend(
+            base_lines[base_position..hunk.base_range.start]
+                .iter()
+                .copied(),
+        );
+        text.extend(side_lines[hunk.side_range.clone()].iter().copied());
+        base_position = hunk.base_range.end;
+    }
+    text.extend(base_lines[base_position..region.end].iter().copied());
+    text
+}
+
+fn push_with_newline(text: &mut String, lines: &str) {
+    text.push_str(lines);
+    if !lines.is_empty() && !lines.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge3;
+
+    const BASE: &str = "a\nb\nc\nd\ne\n";
+
+    #[test]
+    fn merges_separate_changes() {
+        let merged = merge3(BASE, "a\nB\nc\nd\ne\n", "a\nb\nc\nD\ne\n", "remote");
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "a\nB\nc\nD\ne\n");
+    }
+
+    #[test]
+    fn adjacent_changes_conflict() {
+        // Like git, changes of neighbouring lines are not merged automatically
+        let merged = merge3(BASE, "a\nB\nc\nd\ne\n", "a\nb\nC\nd\ne\n", "remote");
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.text,
+            "a\n<<<<<<< local\nB\nc\n=======\nb\nC\n>>>>>>> remote\nd\ne\n"
+        );
+    }
+
+    #[test]
+    fn identical_changes_do_not_conflict() {
+        let merged = merge3(BASE, "a\nB\nc\nd\ne\nf\n", "a\nB\nc\nd\ne\nf\n", "remote");
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "a\nB\nc\nd\ne\nf\n");
+    }
+
+    #[test]
+    fn identical_and_separate_changes() {
+        let merged = merge3(BASE, "a\nB\nc\nd\ne\n", "a\nB\nc\nD\ne\n", "remote");
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "a\nB\nc\nD\ne\n");
+    }
+
+    #[test]
+    fn insert_at_end_of_file() {
+        let merged = merge3(BASE, "A\nb\nc\nd\ne\n", "a\nb\nc\nd\ne\nf\n", "remote");
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "A\nb\nc\nd\ne\nf\n");
+    }
+
+    #[test]
+    fn different_inserts_at_end_of_file_conflict() {
+        let merged = merge3(BASE, "a\nb\nc\nd\ne\nf\n", "a\nb\nc\nd\ne\ng\n", "remote");
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.text,
+            "a\nb\nc\nd\ne\n<<<<<<< local\nf\n=======\ng\n>>>>>>> remote\n"
+        );
+    }
+
+    #[test]
+    fn insert_at_end_of_file_without_final_newline() {
+        let merged = merge3("a\nb", "a\nb\nc", "A\nb", "remote");
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "A\nb\nc");
+    }
+}
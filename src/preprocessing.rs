@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::WrapErr;
+use near_primitives::types::AccountId;
+
+use crate::socialdb_types::ComponentName;
+
+const INCLUDE_DIRECTIVE: &str = "// @include ";
+
+/// Replaces `// @include <path>` lines with the content of the file at `<path>`
+/// (relative to the including file), recursively. Runs on the raw source code, before TypeScript
+/// types are stripped: the type stripping does not keep all the comments. Included TypeScript
+/// files are stripped separately when the including component is plain JSX.
+pub fn expand_includes(filepath: &Path, code: &str) -> color_eyre::eyre::Result<String> {
+    expand_includes_recursively(
+        filepath,
+        code,
+        !crate::typescript::is_typescript_file(filepath),
+        &mut vec![],
+    )
+}
+
+fn expand_includes_recursively(
+    filepath: &Path,
+    code: &str,
+    strip_included_types: bool,
+    include_stack: &mut Vec<PathBuf>,
+) -> color_eyre::eyre::Result<String> {
+    if !code.contains(INCLUDE_DIRECTIVE.trim_end()) {
+        return Ok(code.to_string());
+    }
+    let mut expanded_code = String::with_capacity(code.len());
+    for (line_index, line) in code.split_inclusive('\n').enumerate() {
+        let Some(include_path) = line.trim().strip_prefix(INCLUDE_DIRECTIVE) else {
+            // A directive that is not on its own line would silently stay a comment
+            if line.contains(INCLUDE_DIRECTIVE.trim_end()) {
+                color_eyre::eyre::bail!(
+                    "{}:{}: `{}` must be on its own line, followed by the path of the included file",
+                    filepath.display(),
+                    line_index + 1,
+                    INCLUDE_DIRECTIVE.trim_end()
+                );
+            }
+            expanded_code.push_str(line);
+            continue;
+        };
+        let include_path = filepath
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(include_path.trim());
+        let include_path = include_path.canonicalize().wrap_err_with(|| {
+            format!(
+                "Failed to find file {} included from {}",
+                include_path.display(),
+                filepath.display()
+            )
+        })?;
+        if include_stack.contains(&include_path) {
+            color_eyre::eyre::bail!(
+                "Circular include of {} from {}",
+                include_path.display(),
+                filepath.display()
+            );
+        }
+        let included_code = std::fs::read_to_string(&include_path).wrap_err_with(|| {
+            format!(
+                "Failed to read file {} included from {}",
+                include_path.display(),
+                filepath.display()
+            )
+        })?;
+
+        include_stack.push(include_path.clone());
+        let included_code = expand_includes_recursively(
+            &include_path,
+            &included_code,
+            strip_included_types,
+            include_stack,
+        )?;
+        include_stack.pop();
+
+        let included_code =
+            if strip_included_types && crate::typescript::is_typescript_file(&include_path) {
+                crate::typescript::strip_types(&include_path, &included_code)?
+            } else {
+                included_code
+            };
+
+        expanded_code.push_str(&included_code);
+        if line.ends_with('\n') && !included_code.ends_with('\n') {
+            expanded_code.push('\n');
+        }
+    }
+    Ok(expanded_code)
+}
+
+/// Files included by the source file with `// @include`, transitively. The files that cannot be
+/// read are listed too, so that their creation is noticed.
+pub fn included_files(filepath: &Path) -> Vec<PathBuf> {
+    let mut included_files = vec![];
+    collect_included_files(filepath, &mut included_files);
+    included_files
+}
+
+fn collect_included_files(filepath: &Path, included_files: &mut Vec<PathBuf>) {
+    let Ok(code) = std::fs::read_to_string(filepath) else {
+        return;
+    };
+    for line in code.lines() {
+        let Some(include_path) = line.trim().strip_prefix(INCLUDE_DIRECTIVE) else {
+            continue;
+        };
+        let include_path = filepath
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(include_path.trim());
+        let include_path = include_path.canonicalize().unwrap_or(include_path);
+        if !included_files.contains(&include_path) {
+            included_files.push(include_path.clone());
+            collect_included_files(&include_path, included_files);
+        }
+    }
+}
+
+/// Replaces `VM.require("<account_id>/widget/<name>")` calls of local components with
+/// immediately invoked functions containing the code of the required component
+pub fn inline_local_requires(
+    account_id: &AccountId,
+    components_code: &HashMap<ComponentName, String>,
+) -> color_eyre::eyre::Result<HashMap<ComponentName, String>> {
+    let mut inlined_components_code = HashMap::new();
+    for component_name in components_code.keys() {
+        inline_local_requires_recursively(
+            account_id,
+            component_name,
+            components_code,
+            &mut inlined_components_code,
+            &mut vec![],
+        )?;
+    }
+    Ok(inlined_components_code)
+}
+
+fn inline_local_requires_recursively(
+    account_id: &AccountId,
+    component_name: &ComponentName,
+    components_code: &HashMap<ComponentName, String>,
+    inlined_components_code: &mut HashMap<ComponentName, String>,
+    require_stack: &mut Vec<ComponentName>,
+) -> color_eyre::eyre::Result<String> {
+    if let Some(code) = inlined_components_code.get(component_name) {
+        return Ok(code.clone());
+    }
+    if require_stack.contains(component_name) {
+        color_eyre::eyre::bail!(
+            "Circular VM.require of <{component_name}>: {}",
+            require_stack.join(" -> ")
+        );
+    }
+    let code = &components_code[component_name];
+    let local_component_prefix = format!("{account_id}/widget/");
+
+    require_stack.push(component_name.clone());
+    let mut inlined_code = code.clone();
+    let mut vm_require_calls = crate::jsx::get_vm_require_calls(component_name, code)?;
+    vm_require_calls.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    for (range, src) in vm_require_calls {
+        // Pinned versions (`...@<block height>`) are not replaced with the local code
+        let Some(required_component_name) = src.strip_prefix(&local_component_prefix) else {
+            continue;
+        };
+        if !components_code.contains_key(required_component_name) {
+            continue;
+        }
+        let required_code = inline_local_requires_recursively(
+            account_id,
+            &required_component_name.to_string(),
+            components_code,
+            inlined_components_code,
+            require_stack,
+        )?;
+        inlined_code.replace_range(range, &format!("(() => {{\n{required_code}\n}})()"));
+    }
+    require_stack.pop();
+
+    inlined_components_code.insert(component_name.clone(), inlined_code.clone());
+    Ok(inlined_code)
+}
+
+/// `${config.<key>}` or `${env:<NAME>}` placeholder in the component source code
+#[derive(Debug, PartialEq, Eq)]
+enum Placeholder<'a> {
+    Config(&'a str),
+    Env(&'a str),
+}
+
+/// The placeholders in the code with their byte ranges. Other `${...}` expressions are skipped.
+fn find_placeholders(code: &str) -> Vec<(std::ops::Range<usize>, Placeholder<'_>)> {
+    let mut placeholders = vec![];
+    let mut position = 0;
+    while let Some(placeholder_start) = code[position..].find("${").map(|start| position + start) {
+        let Some(placeholder_end) = code[placeholder_start..]
+            .find('}')
+            .map(|end| placeholder_start + end)
+        else {
+            break;
+        };
+        let name = &code[placeholder_start + 2..placeholder_end];
+        let placeholder = if let Some(key) = name.strip_prefix("config.") {
+            Some(Placeholder::Config(key))
+        } else {
+            name.strip_prefix("env:").map(Placeholder::Env)
+        };
+        if let Some(placeholder) = placeholder {
+            placeholders.push((placeholder_start..placeholder_end + 1, placeholder));
+        }
+        position = placeholder_end + 1;
+    }
+    placeholders
+}
+
+/// Replaces the `${config.<key>}` and `${env:<NAME>}` placeholders with identifiers of the same
+/// length, so that the source code can be parsed with the syntax errors at their positions
+pub fn mask_variables(code: &str) -> String {
+    let mut masked_code = code.to_string();
+    for (range, _) in find_placeholders(code).into_iter().rev() {
+        let length = code[range.clone()].chars().count();
+        masked_code.replace_range(range, &"_".repeat(length));
+    }
+    masked_code
+}
+
+/// Whether the source code is changed before deploy (TypeScript types, includes or placeholders
+/// with values), so that it cannot be compared with the deployed code line by line
+pub fn needs_preprocessing(
+    filepath: &Path,
+    code: &str,
+    variables: &HashMap<String, String>,
+) -> bool {
+    crate::typescript::is_typescript_file(filepath)
+        || code.contains(INCLUDE_DIRECTIVE.trim_end())
+        || find_placeholders(code)
+            .iter()
+            .any(|(_, placeholder)| match placeholder {
+                Placeholder::Config(key) => variables.contains_key(*key),
+                Placeholder::Env(_) => true,
+            })
+}
+
+/// Replaces `${config.<key>}` placeholders with the values from `variables` and `${env:<NAME>}`
+/// placeholders with environment variables (only the ones with the `BOS_` prefix, as the values
+/// are published on-chain). Keys that are not in `variables` and other `${...}` expressions are
+/// left as is: they are regular template literal expressions (e.g. of a local `config` object).
+pub fn render_variables(
+    component_name: &str,
+    code: &str,
+    variables: &HashMap<String, String>,
+) -> color_eyre::eyre::Result<String> {
+    let mut rendered_code = code.to_string();
+    for (range, placeholder) in find_placeholders(code).into_iter().rev() {
+        let value = match placeholder {
+            Placeholder::Config(key) => match variables.get(key) {
+                Some(value) => value.clone(),
+                None => continue,
+            },
+            Placeholder::Env(env_var_name) => {
+                if !env_var_name.starts_with(crate::consts::COMPONENT_ENV_VAR_PREFIX) {
+                    color_eyre::eyre::bail!(
+                        "Component <{component_name}> uses `${{env:{env_var_name}}}`, but only environment variables with the <{}> prefix can be used in components (their values are published on-chain)",
+                        crate::consts::COMPONENT_ENV_VAR_PREFIX
+                    );
+                }
+                std::env::var(env_var_name).wrap_err_with(|| {
+                    format!("Component <{component_name}> uses `${{env:{env_var_name}}}`, but <{env_var_name}> environment variable is not set")
+                })?
+            }
+        };
+        rendered_code.replace_range(range, &value);
+    }
+    Ok(rendered_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    use super::{mask_variables, needs_preprocessing, render_variables};
+
+    fn variables() -> HashMap<String, String> {
+        HashMap::from([("account".to_string(), "example.testnet".to_string())])
+    }
+
+    #[test]
+    fn renders_config_values() {
+        let code = "const a = \"${config.account}\";\nreturn <Widget src={`${config.account}/widget/A`} />;";
+        assert_eq!(
+            render_variables("A", code, &variables()).unwrap(),
+            "const a = \"example.testnet\";\nreturn <Widget src={`example.testnet/widget/A`} />;"
+        );
+    }
+
+    #[test]
+    fn leaves_unknown_config_keys_and_other_expressions() {
+        let code = "const config = { base: \"x\" };\nreturn `${config.base}/path ${props.id}`;";
+        assert_eq!(render_variables("A", code, &variables()).unwrap(), code);
+        assert_eq!(render_variables("A", code, &HashMap::new()).unwrap(), code);
+    }
+
+    #[test]
+    fn renders_prefixed_env_vars_only() {
+        std::env::set_var("BOS_TEST_RENDER_HOST", "https://example.com");
+        assert_eq!(
+            render_variables("A", "`${env:BOS_TEST_RENDER_HOST}/x`", &HashMap::new()).unwrap(),
+            "`https://example.com/x`"
+        );
+        assert!(render_variables("A", "${env:BOS_TEST_RENDER_UNSET}", &HashMap::new()).is_err());
+        assert!(render_variables("A", "${env:HOME}", &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn masks_placeholders_keeping_positions() {
+        let code = "const a = ${config.account};\nreturn `${env:BOS_HOST}/${props.id}`;";
+        let masked_code = mask_variables(code);
+        assert_eq!(
+            masked_code,
+            "const a = _________________;\nreturn `_______________/${props.id}`;"
+        );
+        assert_eq!(masked_code.len(), code.len());
+    }
+
+    #[test]
+    fn detects_preprocessed_sources() {
+        let path = Path::new("src/A.jsx");
+        assert!(!needs_preprocessing(path, "return <div />;", &variables()));
+        assert!(needs_preprocessing(
+            Path::new("src/A.tsx"),
+            "return <div />;",
+            &variables()
+        ));
+        assert!(needs_preprocessing(
+            path,
+            "// @include lib.js\nreturn 1;",
+            &variables()
+        ));
+        assert!(needs_preprocessing(
+            path,
+            "return \"${config.account}\";",
+            &variables()
+        ));
+        assert!(needs_preprocessing(
+            path,
+            "return `${env:BOS_HOST}`;",
+            &variables()
+        ));
+        assert!(!needs_preprocessing(
+            path,
+            "return `${config.base}`;",
+            &variables()
+        ));
+    }
+}
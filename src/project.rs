@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{ContextCompat, WrapErr};
+use near_primitives::types::AccountId;
+
+/// Project manifest (`bos.toml`) located in the project root.
+///
+/// Example:
+/// ```toml
+/// src = "widgets"
+/// include = ["**/*.jsx", "**/*.tsx", "**/*.ts"]
+/// exclude = ["drafts/**"]
+/// account = "example.near"
+/// inline_local_requires = true
+/// deploy_batch_max_bytes = 102400
+/// deploy_batch_max_components = 50
+///
+/// [config]
+/// greeter = "greeter.near"
+///
+/// [networks.testnet]
+/// account = "example.testnet"
+/// signer = "deployer.testnet"
+///
+/// [networks.testnet.config]
+/// greeter = "greeter.testnet"
+///
+/// [networks.localnet]
+/// account = "example.test.near"
+/// social_db_contract_account_id = "social.test.near"
+/// ```
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectManifest {
+    #[serde(default = "default_src")]
+    pub src: PathBuf,
+    #[serde(default = "default_include")]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub account: Option<AccountId>,
+    pub signer: Option<AccountId>,
+    /// Inline the code of local components required with `VM.require` on deploy
+    #[serde(default)]
+    pub inline_local_requires: bool,
+    /// Maximum size of the `set` arguments of a single deploy transaction (in bytes)
+    pub deploy_batch_max_bytes: Option<usize>,
+    /// Maximum number of components written by a single deploy transaction (limits its gas)
+    pub deploy_batch_max_components: Option<usize>,
+    /// Values for `${config.<key>}` placeholders in component sources
+    #[serde(default)]
+    pub config: HashMap<String, String>,
+    #[serde(default)]
+    pub networks: HashMap<String, NetworkManifest>,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkManifest {
+    pub account: Option<AccountId>,
+    pub signer: Option<AccountId>,
+    pub social_db_contract_account_id: Option<AccountId>,
+    /// Network-specific values for `${config.<key>}` placeholders
+    #[serde(default)]
+    pub config: HashMap<String, String>,
+}
+
+impl Default for ProjectManifest {
+    fn default() -> Self {
+        Self {
+            src: default_src(),
+            include: default_include(),
+            exclude: vec![],
+            account: None,
+            signer: None,
+            inline_local_requires: false,
+            deploy_batch_max_bytes: None,
+            deploy_batch_max_components: None,
+            config: HashMap::new(),
+            networks: HashMap::new(),
+        }
+    }
+}
+
+fn default_src() -> PathBuf {
+    PathBuf::from("src")
+}
+
+/// Plain `.ts` files are usually helper or type modules, so they are only components when included
+/// explicitly in `bos.toml`
+fn default_include() -> Vec<String> {
+    vec!["**/*.jsx".to_string(), "**/*.tsx".to_string()]
+}
+
+#[derive(Debug, Clone)]
+pub struct Project {
+    /// The folder with `bos.toml`, or the current folder if there is no manifest
+    pub root: PathBuf,
+    pub manifest: ProjectManifest,
+}
+
+impl Project {
+    /// Looks for `bos.toml` in the current folder and its parents
+    pub fn load() -> color_eyre::eyre::Result<Self> {
+        let current_dir =
+            std::env::current_dir().wrap_err("Failed to get the current working directory")?;
+        for dir in current_dir.ancestors() {
+            let manifest_path = dir.join(crate::consts::PROJECT_MANIFEST_FILE_NAME);
+            if manifest_path.is_file() {
+                return Self::from_manifest_path(&manifest_path);
+            }
+        }
+        Ok(Self {
+            root: current_dir,
+            manifest: ProjectManifest::default(),
+        })
+    }
+
+    fn from_manifest_path(manifest_path: &Path) -> color_eyre::eyre::Result<Self> {
+        let manifest_content = std::fs::read_to_string(manifest_path).wrap_err_with(|| {
+            format!(
+                "Failed to read project manifest from {}",
+                manifest_path.display()
+            )
+        })?;
+        let manifest: ProjectManifest = toml::from_str(&manifest_content).wrap_err_with(|| {
+            format!(
+                "Failed to parse project manifest from {}",
+                manifest_path.display()
+            )
+        })?;
+        Ok(Self {
+            root: manifest_path
+                .parent()
+                .wrap_err("Internal error: project manifest path has no parent folder")?
+                .to_path_buf(),
+            manifest,
+        })
+    }
+
+    pub fn src_dir(&self) -> PathBuf {
+        self.root.join(&self.manifest.src)
+    }
+
+    /// The path relative to the project root (for messages), or the path itself if it is outside
+    pub fn relative_path<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+
+    /// Whether the components are deployed to the account on any network (as configured in
+    /// `bos.toml`)
+    pub fn is_project_account(&self, account_id: &AccountId) -> bool {
+        self.manifest.account.as_ref() == Some(account_id)
+            || self
+                .manifest
+                .networks
+                .values()
+                .any(|network| network.account.as_ref() == Some(account_id))
+    }
+
+    pub fn bos_file_path(&self) -> PathBuf {
+        self.src_dir().join(".bos")
+    }
+
+    /// Component source files under the source root that match `include` and do not match `exclude`
+    pub fn component_files(&self) -> color_eyre::eyre::Result<Vec<PathBuf>> {
+        let src_dir = self.src_dir();
+        let exclude = self
+            .manifest
+            .exclude
+            .iter()
+            .map(|pattern| {
+                glob::Pattern::new(pattern)
+                    .wrap_err_with(|| format!("Invalid exclude pattern in bos.toml: {pattern}"))
+            })
+            .collect::<color_eyre::eyre::Result<Vec<_>>>()?;
+
+        let mut component_files = vec![];
+        for pattern in &self.manifest.include {
+            let full_pattern = src_dir.join(pattern);
+            let full_pattern = full_pattern.to_str().wrap_err_with(|| {
+                format!("Include pattern cannot be presented as UTF-8: {pattern}")
+            })?;
+            for component_filepath in glob::glob(full_pattern)
+                .wrap_err_with(|| format!("Invalid include pattern in bos.toml: {pattern}"))?
+                .filter_map(Result::ok)
+            {
+                let relative_path = component_filepath.strip_prefix(&src_dir)?;
+                // TypeScript declaration files are not components
+                if relative_path.to_string_lossy().ends_with(".d.ts") {
+                    continue;
+                }
+                if exclude
+                    .iter()
+                    .any(|pattern| pattern.matches_path(relative_path))
+                {
+                    continue;
+                }
+                if !component_files.contains(&component_filepath) {
+                    component_files.push(component_filepath);
+                }
+            }
+        }
+        Ok(component_files)
+    }
+
+    /// Component name for a source file: the path relative to the source root without the extension,
+    /// with folders separated by `.`
+    pub fn component_name(
+        &self,
+        component_filepath: &Path,
+    ) -> color_eyre::eyre::Result<crate::socialdb_types::ComponentName> {
+        Ok(component_filepath
+            .strip_prefix(self.src_dir())?
+            .with_extension("")
+            .to_str()
+            .wrap_err_with(|| {
+                format!(
+                    "Component name cannot be presented as UTF-8: {}",
+                    component_filepath.display()
+                )
+            })?
+            .replace(std::path::MAIN_SEPARATOR, "."))
+    }
+
+    pub fn network(&self, network_name: &str) -> Option<&NetworkManifest> {
+        self.manifest.networks.get(network_name)
+    }
+
+    /// Values for `${config.<key>}` placeholders on the given network
+    pub fn variables(&self, network_name: &str) -> HashMap<String, String> {
+        let mut variables = self.manifest.config.clone();
+        if let Some(network) = self.network(network_name) {
+            variables.extend(network.config.clone());
+        }
+        variables
+    }
+
+    /// All the target accounts configured in the manifest together with their networks
+    pub fn accounts(&self) -> Vec<(AccountId, Option<String>)> {
+        let mut network_names = self.manifest.networks.keys().collect::<Vec<_>>();
+        network_names.sort();
+        let mut accounts = network_names
+            .into_iter()
+            .filter_map(|network_name| {
+                self.manifest.networks[network_name]
+                    .account
+                    .clone()
+                    .map(|account_id| (account_id, Some(network_name.clone())))
+            })
+            .collect::<Vec<_>>();
+        if let Some(account_id) = &self.manifest.account {
+            if !accounts
+                .iter()
+                .any(|(configured, _)| configured == account_id)
+            {
+                accounts.insert(0, (account_id.clone(), None));
+            }
+        }
+        accounts
+    }
+
+    /// The signer configured for the network that deploys to the given account, or the default signer
+    pub fn signer_account_id(&self, account_id: &AccountId) -> Option<AccountId> {
+        self.manifest
+            .networks
+            .values()
+            .filter(|network| network.account.as_ref() == Some(account_id))
+            .find_map(|network| network.signer.clone())
+            .or_else(|| self.manifest.signer.clone())
+    }
+}
+
+/// Offers the accounts configured in `bos.toml` before falling back to the regular account input
+pub fn input_account_id(
+    message: &str,
+    input_account_id_from_used_account_list: impl FnOnce() -> color_eyre::eyre::Result<
+        Option<near_cli_rs::types::account_id::AccountId>,
+    >,
+) -> color_eyre::eyre::Result<Option<near_cli_rs::types::account_id::AccountId>> {
+    let accounts = Project::load()?.accounts();
+    if accounts.is_empty() {
+        return input_account_id_from_used_account_list();
+    }
+    let enter_another_account = "Enter another account ID".to_string();
+    let mut options = accounts
+        .iter()
+        .map(|(account_id, network_name)| match network_name {
+            Some(network_name) => format!("{account_id} ({network_name})"),
+            None => account_id.to_string(),
+        })
+        .collect::<Vec<_>>();
+    options.push(enter_another_account.clone());
+    let selected = inquire::Select::new(message, options.clone()).prompt()?;
+    if selected == enter_another_account {
+        return input_account_id_from_used_account_list();
+    }
+    let selected_index = options
+        .iter()
+        .position(|option| option == &selected)
+        .wrap_err("Internal error: selected account is not in the list")?;
+    Ok(Some(accounts[selected_index].0.clone().into()))
+}
@@ -38,8 +38,7 @@ impl DeleteContext {
             let key = scope.key.clone();
 
             move |network_config| {
-                let near_social_account_id = crate::consts::NEAR_SOCIAL_ACCOUNT_ID.get(network_config.network_name.as_str())
-                    .wrap_err_with(|| format!("The <{}> network does not have a near-social contract.", network_config.network_name))?;
+                let near_social_account_id = &crate::common::get_near_social_account_id(network_config)?;
 
                 let input_args = serde_json::to_string(&crate::socialdb_types::SocialDbQuery {
                     keys: vec![format!("{key}")],
@@ -1,4 +1,4 @@
-use color_eyre::eyre::{ContextCompat, WrapErr};
+use color_eyre::eyre::WrapErr;
 use inquire::{CustomType, Select};
 use near_cli_rs::common::{CallResultExt, JsonRpcClientExt};
 use std::sync::Arc;
@@ -33,8 +33,7 @@ impl SignerContext {
             let set_to_account_id = set_to_account_id.clone();
 
             move |network_config| {
-                let near_social_account_id = crate::consts::NEAR_SOCIAL_ACCOUNT_ID.get(network_config.network_name.as_str())
-                    .wrap_err_with(|| format!("The <{}> network does not have a near-social contract.", network_config.network_name))?;
+                let near_social_account_id = &crate::common::get_near_social_account_id(network_config)?;
                 let key = previous_context.key.clone();
                 let input_args = serde_json::to_string(&crate::socialdb_types::SocialDbQuery {
                     keys: vec![format!("{key}")],
@@ -25,17 +25,8 @@ impl ViewContext {
                 let key = scope.key.clone();
 
                 move |network_config| {
-                    let near_social_account_id = match crate::consts::NEAR_SOCIAL_ACCOUNT_ID
-                        .get(&network_config.network_name.as_str())
-                    {
-                        Some(account_id) => account_id,
-                        None => {
-                            return Err(color_eyre::Report::msg(format!(
-                                "The <{}> network does not have a near-social contract.",
-                                network_config.network_name
-                            )))
-                        }
-                    };
+                    let near_social_account_id =
+                        &crate::common::get_near_social_account_id(network_config)?;
 
                     let input_args = serde_json::to_string(&crate::socialdb_types::SocialDbQuery {
                         keys: vec![format!("{key}")],
@@ -1,5 +1,3 @@
-use color_eyre::eyre::ContextCompat;
-
 #[derive(Debug, Clone, interactive_clap::InteractiveClap)]
 #[interactive_clap(input_context = near_cli_rs::GlobalContext)]
 #[interactive_clap(output_context = ManageProfileContext)]
@@ -18,16 +16,7 @@ impl ManageProfileContext {
         previous_context: near_cli_rs::GlobalContext,
         _scope: &<ManageProfile as interactive_clap::ToInteractiveClapContextScope>::InteractiveClapContextScope,
     ) -> color_eyre::eyre::Result<Self> {
-        let get_contract_account_id: near_cli_rs::commands::account::storage_management::GetContractAccountId = std::sync::Arc::new(
-            move |network_config|
-            crate::consts::NEAR_SOCIAL_ACCOUNT_ID
-                .get(&network_config.network_name.as_str())
-                .cloned()
-                .wrap_err_with(|| format!(
-                    "The <{}> network does not have a near-social contract.",
-                    network_config.network_name
-                ))
-        );
+        let get_contract_account_id: near_cli_rs::commands::account::storage_management::GetContractAccountId = std::sync::Arc::new(crate::common::get_near_social_account_id);
         Ok(Self(
             near_cli_rs::commands::account::profile_management::ContractContext {
                 global_context: previous_context,
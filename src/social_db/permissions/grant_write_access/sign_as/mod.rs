@@ -1,5 +1,3 @@
-use color_eyre::eyre::ContextCompat;
-
 #[derive(Debug, Clone, interactive_clap::InteractiveClap)]
 #[interactive_clap(input_context = super::storage_deposit::ExtraStorageDepositContext)]
 #[interactive_clap(output_context = SignerContext)]
@@ -48,8 +46,7 @@ impl From<SignerContext> for near_cli_rs::commands::ActionContext {
 
         let on_after_getting_network_callback: near_cli_rs::commands::OnAfterGettingNetworkCallback = std::sync::Arc::new({
             move |network_config| {
-                let near_social_account_id = crate::consts::NEAR_SOCIAL_ACCOUNT_ID.get(network_config.network_name.as_str())
-                    .wrap_err_with(|| format!("The <{}> network does not have a near-social contract.", network_config.network_name))?;
+                let near_social_account_id = &crate::common::get_near_social_account_id(network_config)?;
                 let args = match &permission_key {
                     near_socialdb_client::PermissionKey::PredecessorId(account_id) => {
                         serde_json::json!({
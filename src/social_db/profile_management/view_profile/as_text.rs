@@ -1,4 +1,4 @@
-use color_eyre::eyre::{ContextCompat, WrapErr};
+use color_eyre::eyre::WrapErr;
 use near_cli_rs::common::{CallResultExt, JsonRpcClientExt};
 
 #[derive(Debug, Clone, interactive_clap::InteractiveClap)]
@@ -23,8 +23,7 @@ impl AsTextContext {
             std::sync::Arc::new({
                 let account_id = account_id.clone();
                 move |network_config, block_reference| {
-                    let near_social_account_id = crate::consts::NEAR_SOCIAL_ACCOUNT_ID.get(network_config.network_name.as_str())
-                        .wrap_err_with(|| format!("The <{}> network does not have a near-social contract.", network_config.network_name))?;
+                    let near_social_account_id = &crate::common::get_near_social_account_id(network_config)?;
 
                     let rpc_query_response = network_config
                         .json_rpc_client()
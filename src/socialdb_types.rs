@@ -19,6 +19,18 @@ pub struct SocialDbQuery {
     pub keys: Vec<String>,
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SocialDbKeys {
+    #[serde(flatten)]
+    pub accounts: HashMap<near_primitives::types::AccountId, SocialDbAccountComponents>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SocialDbAccountComponents {
+    #[serde(rename = "widget")]
+    pub components: HashMap<ComponentName, bool>,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SocialDb {
     #[serde(flatten)]
@@ -59,7 +71,7 @@ impl SocialDbComponent {
     }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
 pub struct SocialDbComponentMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
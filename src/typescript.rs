@@ -0,0 +1,105 @@
+use color_eyre::eyre::ContextCompat;
+use swc_core::common::{
+    comments::SingleThreadedComments, sync::Lrc, FileName, Globals, Mark, SourceMap, GLOBALS,
+};
+use swc_core::ecma::ast::{Decl, Program, Script, Stmt};
+use swc_core::ecma::parser::{Parser, StringInput, Syntax, TsSyntax};
+
+/// BOS components are bodies of a function (they `return` the rendered JSX from the top level),
+/// so the source is wrapped into a function to be parsed as a regular TypeScript program.
+const COMPONENT_WRAPPER_FUNCTION_NAME: &str = "__bos_component__";
+
+pub fn is_typescript_file(filepath: &std::path::Path) -> bool {
+    matches!(
+        filepath
+            .extension()
+            .and_then(|extension| extension.to_str()),
+        Some("ts" | "tsx")
+    )
+}
+
+/// Checks the TypeScript component source code for syntax errors
+pub fn check_syntax(filepath: &std::path::Path, code: &str) -> Result<(), crate::jsx::SyntaxError> {
+    parse(filepath, code).map(|_| ())
+}
+
+/// Strips TypeScript type annotations from the component source code and returns plain JSX
+pub fn strip_types(filepath: &std::path::Path, code: &str) -> color_eyre::eyre::Result<String> {
+    let (source_map, comments, script) = parse(filepath, code).map_err(|error| {
+        color_eyre::eyre::eyre!(
+            "Failed to parse TypeScript component {}:{}:{}: {}",
+            filepath.display(),
+            error.line,
+            error.column,
+            error.message
+        )
+    })?;
+
+    let stripped_program = GLOBALS.set(&Globals::new(), || {
+        let unresolved_mark = Mark::new();
+        let top_level_mark = Mark::new();
+        Program::Script(script)
+            .apply(swc_core::ecma::transforms::base::resolver(
+                unresolved_mark,
+                top_level_mark,
+                true,
+            ))
+            .apply(swc_core::ecma::transforms::typescript::strip(
+                unresolved_mark,
+                top_level_mark,
+            ))
+    });
+
+    let component_body = match stripped_program {
+        Program::Script(Script { mut body, .. }) if body.len() == 1 => match body.remove(0) {
+            Stmt::Decl(Decl::Fn(function_declaration)) => function_declaration.function.body,
+            _ => None,
+        },
+        _ => None,
+    }
+    .wrap_err("Internal error: unexpected structure of the type-stripped component")?;
+
+    Ok(swc_core::ecma::codegen::to_code_default(
+        source_map,
+        Some(&comments),
+        &Script {
+            span: component_body.span,
+            body: component_body.stmts,
+            shebang: None,
+        },
+    ))
+}
+
+fn parse(
+    filepath: &std::path::Path,
+    code: &str,
+) -> Result<(Lrc<SourceMap>, SingleThreadedComments, Script), crate::jsx::SyntaxError> {
+    let tsx = filepath
+        .extension()
+        .and_then(|extension| extension.to_str())
+        == Some("tsx");
+    let source_map: Lrc<SourceMap> = Default::default();
+    let source_file = source_map.new_source_file(
+        Lrc::new(FileName::Real(filepath.to_path_buf())),
+        format!("function {COMPONENT_WRAPPER_FUNCTION_NAME}() {{\n{code}\n}}"),
+    );
+    let comments = SingleThreadedComments::default();
+
+    let mut parser = Parser::new(
+        Syntax::Typescript(TsSyntax {
+            tsx,
+            ..Default::default()
+        }),
+        StringInput::from(&*source_file),
+        Some(&comments),
+    );
+    let script = parser
+        .parse_script()
+        .and_then(|script| match parser.take_errors().into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(script),
+        })
+        // The first line is taken by the wrapper function
+        .map_err(|error| crate::jsx::SyntaxError::from_parser_error(&source_map, &error, 1))?;
+    Ok((source_map, comments, script))
+}